@@ -45,5 +45,36 @@ pub fn bench_adaptive(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_adaptive);
+pub fn bench_fixed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+
+    let message = hex_literal::hex!(
+        "
+        33 a8 bf ca 56 43 43 d5 bc c7 b3 47 30 23 00
+    "
+    );
+    let len = 24;
+
+    group.throughput(Throughput::Bytes(message.len() as u64));
+
+    // the fixed tree never changes, so setup is not part of the benchmark
+    let mut huff = Huffman::fixed();
+
+    group.bench_with_input(
+        BenchmarkId::new("fixed", "client command"),
+        &(&message[..], len),
+        |b, i| {
+            b.iter(|| {
+                let mut decoded_bytes = bytes::BytesMut::new();
+
+                huff.decode(i.0, i.1, &mut decoded_bytes)
+                    .expect("test data should be well-formed");
+            })
+        },
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_adaptive, bench_fixed);
 criterion_main!(benches);
@@ -1,10 +1,12 @@
 use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 use quake3_huffman::Huffman;
 
+const USER_INFO: &[u8] = include_bytes!("../../tests/data/userinfo.txt");
+
 pub fn bench_adaptive(c: &mut Criterion) {
     let mut group = c.benchmark_group("encode");
 
-    let userinfo = b"\"\\challenge\\-9938504\\qport\\2033\\protocol\\68\\name\\UnnamedPlayer\\rate\\25000\\snaps\\20\\model\\sarge\\headmodel\\sarge\\team_model\\james\\team_headmodel\\*james\\color1\\4\\color2\\5\\handicap\\100\\sex\\male\\cl_anonymous\\0\\cg_predictItems\\1\\teamtask\\0\\cl_voipProtocol\\opus\\cl_guid\\D17466611282F45B65CE2FD80F83B6B0\"";
+    let userinfo = &[&b"\""[..], USER_INFO, b"\""].concat();
 
     group.throughput(Throughput::Bytes(userinfo.len() as u64));
 
@@ -24,5 +26,33 @@ pub fn bench_adaptive(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_adaptive);
+pub fn bench_fixed(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encode");
+
+    let message = hex_literal::hex!(
+        "
+        2a 00 00 00 07 00 00 00 03 00 00 00 04 04 00 00
+        00 73 63 6f 72 65 00 05
+    "
+    );
+
+    group.throughput(Throughput::Bytes(message.len() as u64));
+
+    // the fixed tree never changes, so setup is not part of the benchmark
+    let mut huff = Huffman::fixed();
+
+    group.bench_with_input(
+        BenchmarkId::new("fixed", "client command"),
+        &message[..],
+        |b, i| {
+            b.iter(|| {
+                let _ = huff.encode(i);
+            })
+        },
+    );
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_adaptive, bench_fixed);
 criterion_main!(benches);
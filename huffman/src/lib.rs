@@ -31,7 +31,7 @@ struct NodeWeight(u64);
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 struct Symbol(u8);
 
-#[derive(/*Copy, Clone,*/ Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(/*Copy,*/ Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 enum Node {
     NotYetTransmitted {
         parent: Option<NodeIndex>,
@@ -93,7 +93,54 @@ impl Node {
 
 const MAX_SYMBOLS: usize = u8::MAX as usize + 1;
 
-const MAX_NODES: usize = MAX_SYMBOLS * 2 - 1;
+// every inserted symbol turns the NYT node into an internal node with a leaf and a new NYT node
+const MAX_NODES: usize = MAX_SYMBOLS * 2 + 1;
+
+/// Symbol frequencies used to seed the [`Huffman::fixed()`] tree
+///
+/// This is `msg_hData` from `msg.c`, which `MSG_initHuffman` feeds into `Huff_addRef` for every symbol.
+#[rustfmt::skip]
+pub const SYMBOL_FREQUENCIES: [u32; MAX_SYMBOLS] = [
+    250315, 41193, 6292, 7106, 3730, 3750, 6110, 23283,
+    33317, 6950, 7838, 9714, 9257, 17259, 3949, 1778,
+    8288, 1604, 1590, 1663, 1100, 1213, 1238, 1134,
+    1749, 1059, 1246, 1149, 1273, 4486, 2805, 3472,
+    21819, 1159, 1670, 1066, 1043, 1012, 1053, 1070,
+    1726, 888, 1180, 850, 960, 780, 1752, 3296,
+    10630, 4514, 5881, 2685, 4650, 3837, 2093, 1867,
+    2584, 1949, 1972, 940, 1134, 1788, 1670, 1206,
+    5719, 6128, 7222, 6654, 3710, 3795, 1492, 1524,
+    2215, 1140, 1355, 971, 2180, 1248, 1328, 1195,
+    1770, 1078, 1264, 1266, 1168, 965, 1155, 1186,
+    1347, 1228, 1529, 1600, 2617, 2048, 2546, 3275,
+    2410, 3585, 2504, 2800, 2675, 6146, 3663, 2840,
+    14253, 3164, 2221, 1687, 3208, 2739, 3512, 4796,
+    4091, 3515, 5288, 4016, 7937, 6031, 5360, 3924,
+    4892, 3743, 4566, 4807, 5852, 6400, 6225, 8291,
+    23243, 7838, 7073, 8935, 5437, 4483, 3641, 5256,
+    5312, 5328, 5370, 3492, 2458, 1694, 1821, 2121,
+    1916, 1149, 1516, 1367, 1236, 1029, 1258, 1104,
+    1245, 1006, 1149, 1025, 1241, 952, 1287, 997,
+    1713, 1009, 1187, 879, 1099, 929, 1078, 951,
+    1656, 930, 1153, 1030, 1262, 1062, 1214, 1060,
+    1621, 930, 1106, 912, 1034, 892, 1158, 990,
+    1175, 850, 1121, 903, 1087, 920, 1144, 1056,
+    3462, 2240, 4397, 12136, 7758, 1345, 1307, 3278,
+    1950, 886, 1023, 1112, 1077, 1042, 1061, 1071,
+    1484, 1001, 1096, 915, 1052, 995, 1070, 876,
+    1111, 851, 1059, 805, 1112, 923, 1103, 817,
+    1899, 1872, 976, 841, 1127, 956, 1159, 950,
+    7791, 954, 1289, 933, 1127, 3207, 1020, 927,
+    1355, 768, 1040, 745, 952, 805, 1073, 740,
+    1013, 805, 1008, 796, 996, 1057, 11457, 13504,
+];
+
+// adaptive mode updates the tree for every en-/decoded symbol, fixed mode never does after setup
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+enum Mode {
+    Adaptive,
+    Fixed,
+}
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
@@ -110,12 +157,13 @@ enum DecodeErrorKind {
 #[cfg_attr(feature = "std", error(transparent))]
 pub struct DecodeError(#[cfg_attr(feature = "std", from)] DecodeErrorKind);
 
-#[derive(/*Copy, Clone,*/ Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[derive(/*Copy,*/ Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Huffman {
     tree: [Option<Node>; MAX_NODES],
     symbol_index: [Option<NodeIndex>; MAX_SYMBOLS],
     nyt: NodeIndex,
     next: NodeIndex,
+    mode: Mode,
 }
 
 impl Huffman {
@@ -136,6 +184,32 @@ impl Huffman {
             symbol_index,
             nyt,
             next,
+            mode: Mode::Adaptive,
+        }
+    }
+
+    /// Codec with a static tree as used for in-band netchan messages
+    ///
+    /// The tree is built like `MSG_initHuffman` does from [`SYMBOL_FREQUENCIES`],
+    /// and unlike [`Huffman::adaptive()`] it does not change while en-/decoding.
+    /// Building the tree takes a while, so prefer cloning an existing codec.
+    pub fn fixed() -> Self {
+        let mut huff = Self::adaptive();
+
+        for (symbol, &frequency) in SYMBOL_FREQUENCIES.iter().enumerate() {
+            for _ in 0..frequency {
+                huff.insert(Symbol(symbol as u8));
+            }
+        }
+
+        huff.mode = Mode::Fixed;
+        huff
+    }
+
+    #[inline]
+    fn update(&mut self, symbol: Symbol) {
+        if Mode::Adaptive == self.mode {
+            self.insert(symbol);
         }
     }
 
@@ -361,6 +435,35 @@ impl Huffman {
         }
     }
 
+    /// Encode a single symbol by appending its bits
+    ///
+    /// This allows to interleave Huffman-coded symbols with other data like `Huff_offsetTransmit` does.
+    #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
+    #[cfg(feature = "alloc")]
+    pub fn encode_symbol(&mut self, symbol: u8, bits: &mut BitVec<u8, Lsb0>) {
+        let symbol_index = self.symbol_index[symbol as usize];
+
+        if let Some(symbol_index) = symbol_index {
+            //println!("encode symbol path @{}", symbol);
+            self.emit(symbol_index, bits, None);
+        } else {
+            //println!("encode NYT @{}", self.nyt.0);
+            self.emit(self.nyt, bits, None);
+
+            //println!("encode new symbol bits {:#04X}", symbol);
+            bits.push((symbol >> 7) & 1 != 0);
+            bits.push((symbol >> 6) & 1 != 0);
+            bits.push((symbol >> 5) & 1 != 0);
+            bits.push((symbol >> 4) & 1 != 0);
+            bits.push((symbol >> 3) & 1 != 0);
+            bits.push((symbol >> 2) & 1 != 0);
+            bits.push((symbol >> 1) & 1 != 0);
+            bits.push(symbol & 1 != 0);
+        }
+
+        self.update(Symbol(symbol));
+    }
+
     #[cfg_attr(docsrs, doc(cfg(feature = "alloc")))]
     #[cfg(feature = "alloc")]
     pub fn encode(&mut self, bytes: impl AsRef<[u8]>) -> BitVec<u8, Lsb0> {
@@ -372,53 +475,15 @@ impl Huffman {
 
         for symbol in bytes.iter().copied() {
             //println!("encode symbol {:#04X}", symbol);
-            let symbol_index = self.symbol_index[symbol as usize];
-
-            if let Some(symbol_index) = symbol_index {
-                //println!("encode symbol path @{}", symbol);
-                self.emit(symbol_index, &mut bits, None);
-            } else {
-                //println!("encode NYT @{}", self.nyt.0);
-                self.emit(self.nyt, &mut bits, None);
-
-                //println!("encode new symbol bits {:#04X}", symbol);
-                bits.push((symbol >> 7) & 1 != 0);
-                bits.push((symbol >> 6) & 1 != 0);
-                bits.push((symbol >> 5) & 1 != 0);
-                bits.push((symbol >> 4) & 1 != 0);
-                bits.push((symbol >> 3) & 1 != 0);
-                bits.push((symbol >> 2) & 1 != 0);
-                bits.push((symbol >> 1) & 1 != 0);
-                bits.push((symbol >> 0) & 1 != 0);
-            }
-
-            self.insert(Symbol(symbol));
+            self.encode_symbol(symbol, &mut bits);
         }
 
         bits
     }
 
-    pub fn decode<'a, B>(
-        &mut self,
-        bits: B,
-        length: usize,
-        bytes: &mut BytesMut,
-    ) -> Result<(), DecodeError>
-    where
-        B: TryInto<&'a BitSlice<u8, Lsb0>>,
-    {
-        //println!("decode {} bytes", length);
-
-        let bits = bits
-            .try_into()
-            .map_err(|_| DecodeError(DecodeErrorKind::UnadressableBitsError))?;
-        let mut bits = bits.iter().by_vals();
-
-        bytes.reserve(length);
-
+    fn decode_next(&mut self, bits: &mut impl Iterator<Item = bool>) -> Result<u8, DecodeError> {
         let mut node_index = Self::ROOT;
-        let mut written = 0;
-        while written < length {
+        loop {
             let node = self.node_ref(node_index);
             match *node {
                 Node::NotYetTransmitted { .. } => {
@@ -454,22 +519,18 @@ impl Huffman {
                     let b7 = bits
                         .next()
                         .ok_or(DecodeError(DecodeErrorKind::NoMoreBits))?;
-                    value |= (b7 as u8) << 0;
+                    value |= b7 as u8;
 
                     //println!("decode NYT {:#04X}", value);
-                    bytes.put_u8(value);
-                    written += 1;
-                    self.insert(Symbol(value));
-                    node_index = Self::ROOT;
+                    self.update(Symbol(value));
                     //println!("---");
+                    return Ok(value);
                 }
                 Node::Leaf { symbol, .. } => {
                     //println!("decode leaf {:#04X}", symbol.0);
-                    bytes.put_u8(symbol.0);
-                    written += 1;
-                    self.insert(symbol);
-                    node_index = Self::ROOT;
+                    self.update(symbol);
                     //println!("---");
+                    return Ok(symbol.0);
                 }
                 Node::Internal { left, right, .. } => {
                     let bit = bits
@@ -481,15 +542,59 @@ impl Huffman {
                 }
             }
         }
+    }
+
+    pub fn decode<'a, B>(
+        &mut self,
+        bits: B,
+        length: usize,
+        bytes: &mut BytesMut,
+    ) -> Result<(), DecodeError>
+    where
+        B: TryInto<&'a BitSlice<u8, Lsb0>>,
+    {
+        //println!("decode {} bytes", length);
+
+        let bits = bits
+            .try_into()
+            .map_err(|_| DecodeError(DecodeErrorKind::UnadressableBitsError))?;
+        let mut bits = bits.iter().by_vals();
+
+        bytes.reserve(length);
+
+        for _ in 0..length {
+            let symbol = self.decode_next(&mut bits)?;
+            bytes.put_u8(symbol);
+        }
 
         Ok(())
     }
+
+    /// Decode a single symbol from the start of `bits`
+    ///
+    /// Returns the symbol and how many bits it took, so that callers can interleave
+    /// Huffman-coded symbols with other data like `Huff_offsetReceive` does.
+    pub fn decode_symbol<'a, B>(&mut self, bits: B) -> Result<(u8, usize), DecodeError>
+    where
+        B: TryInto<&'a BitSlice<u8, Lsb0>>,
+    {
+        let bits = bits
+            .try_into()
+            .map_err(|_| DecodeError(DecodeErrorKind::UnadressableBitsError))?;
+        let mut iter = bits.iter().by_vals();
+
+        let symbol = self.decode_next(&mut iter)?;
+
+        Ok((symbol, bits.len() - iter.len()))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bitvec::slice::BitSlice;
+
+    // userinfo from a wireshark dump of `connect`, which quotes it
+    const USER_INFO: &[u8] = include_bytes!("../../tests/data/userinfo.txt");
 
     #[cfg(feature = "alloc")]
     #[test]
@@ -533,9 +638,9 @@ mod tests {
     fn huffman_adaptive_encode() {
         let mut huff = Huffman::adaptive();
 
-        let decoded = b"\"\\challenge\\-9938504\\qport\\2033\\protocol\\68\\name\\UnnamedPlayer\\rate\\25000\\snaps\\20\\model\\sarge\\headmodel\\sarge\\team_model\\james\\team_headmodel\\*james\\color1\\4\\color2\\5\\handicap\\100\\sex\\male\\cl_anonymous\\0\\cg_predictItems\\1\\teamtask\\0\\cl_voipProtocol\\opus\\cl_guid\\D17466611282F45B65CE2FD80F83B6B0\"";
+        let decoded = [&b"\""[..], USER_INFO, b"\""].concat();
 
-        let bits = huff.encode(decoded);
+        let bits = huff.encode(&decoded);
 
         let expected = hex_literal::hex!(
             "
@@ -589,7 +694,7 @@ mod tests {
 
         huff.decode(&encoded_bytes[..], decoded_len, &mut decoded_bytes)?;
 
-        let expected = b"\"\\challenge\\-9938504\\qport\\2033\\protocol\\68\\name\\UnnamedPlayer\\rate\\25000\\snaps\\20\\model\\sarge\\headmodel\\sarge\\team_model\\james\\team_headmodel\\*james\\color1\\4\\color2\\5\\handicap\\100\\sex\\male\\cl_anonymous\\0\\cg_predictItems\\1\\teamtask\\0\\cl_voipProtocol\\opus\\cl_guid\\D17466611282F45B65CE2FD80F83B6B0\"";
+        let expected = [&b"\""[..], USER_INFO, b"\""].concat();
         assert_eq!(&decoded_bytes[..], expected);
 
         Ok(())
@@ -605,6 +710,136 @@ mod tests {
 
         assert!(!buf.is_empty());
 
+        Ok(())
+    }
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn huffman_adaptive_roundtrip_all_symbols() -> Result<(), DecodeError> {
+        let decoded: Vec<u8> = (0..=u8::MAX).collect();
+
+        let bits = Huffman::adaptive().encode(&decoded);
+
+        let mut decoded_bytes = BytesMut::new();
+        Huffman::adaptive().decode(bits.as_bitslice(), decoded.len(), &mut decoded_bytes)?;
+
+        assert_eq!(&decoded_bytes[..], &decoded[..]);
+
+        Ok(())
+    }
+
+    // clc_clientCommand "score" with serverId, messageAcknowledge and reliableAcknowledge in front
+    const FIXED_DECODED: [u8; 24] = hex_literal::hex!(
+        "
+        2a 00 00 00 07 00 00 00 03 00 00 00 04 04 00 00
+        00 73 63 6f 72 65 00 05
+    "
+    );
+
+    // generated with a port of ioq3 huffman.c seeded from msg_hData, huffman_fixed_optimal_code() checks the tree independently
+    // TODO: replace with a clc_clientCommand captured from a protocol 68 client
+    const FIXED_ENCODED: [u8; 15] = hex_literal::hex!(
+        "
+        33 a8 bf ca 56 43 43 d5 bc c7 b3 47 30 23 00
+    "
+    );
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn huffman_fixed_encode() {
+        let mut huff = Huffman::fixed();
+
+        let bits = huff.encode(FIXED_DECODED);
+        assert_eq!(bits.as_raw_slice(), &FIXED_ENCODED[..]);
+
+        // the tree does not change, so encoding again yields the same bits
+        let bits = huff.encode(FIXED_DECODED);
+        assert_eq!(bits.as_raw_slice(), &FIXED_ENCODED[..]);
+    }
+
+    #[test]
+    fn huffman_fixed_decode() -> Result<(), DecodeError> {
+        let mut huff = Huffman::fixed();
+
+        let mut decoded_bytes = BytesMut::new();
+        huff.decode(&FIXED_ENCODED[..], FIXED_DECODED.len(), &mut decoded_bytes)?;
+        assert_eq!(&decoded_bytes[..], &FIXED_DECODED[..]);
+
+        let mut decoded_bytes = BytesMut::new();
+        huff.decode(&FIXED_ENCODED[..], FIXED_DECODED.len(), &mut decoded_bytes)?;
+        assert_eq!(&decoded_bytes[..], &FIXED_DECODED[..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn huffman_fixed_optimal_code() {
+        let huff = Huffman::fixed();
+
+        let depth = |mut index| {
+            let mut depth = 0u64;
+            while let Some(parent) = huff.node_ref(index).parent() {
+                index = parent;
+                depth += 1;
+            }
+            depth
+        };
+        let cost: u64 = SYMBOL_FREQUENCIES
+            .iter()
+            .enumerate()
+            .map(|(symbol, &frequency)| {
+                let leaf = huff.symbol_index[symbol].expect("all symbols should be in the tree");
+                u64::from(frequency) * depth(leaf)
+            })
+            .sum();
+
+        // textbook Huffman construction, independent of the insert machinery
+        let mut weights: std::collections::BinaryHeap<core::cmp::Reverse<u64>> = SYMBOL_FREQUENCIES
+            .iter()
+            .map(|&frequency| core::cmp::Reverse(u64::from(frequency)))
+            .chain([core::cmp::Reverse(0)])
+            .collect();
+        let mut optimal_cost = 0;
+        while weights.len() > 1 {
+            let core::cmp::Reverse(a) = weights.pop().unwrap();
+            let core::cmp::Reverse(b) = weights.pop().unwrap();
+            optimal_cost += a + b;
+            weights.push(core::cmp::Reverse(a + b));
+        }
+
+        assert_eq!(cost, optimal_cost);
+    }
+
+    #[test]
+    fn huffman_fixed_most_frequent_symbol() {
+        let huff = Huffman::fixed();
+
+        // msg_hData weighs \0 the most, so its leaf hangs right below the root
+        let leaf = huff.symbol_index[0].expect("all symbols should be in the tree");
+        let parent = huff
+            .node_ref(leaf)
+            .parent()
+            .expect("leaf should have a parent");
+        assert_eq!(huff.node_ref(parent).parent(), Some(Huffman::ROOT));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn huffman_fixed_symbol_roundtrip() -> Result<(), DecodeError> {
+        let mut huff = Huffman::fixed();
+
+        let mut bits: BitVec<u8, Lsb0> = BitVec::new();
+        bits.push(true);
+        huff.encode_symbol(0x2A, &mut bits);
+        let symbol_len = bits.len() - 1;
+        bits.push(false);
+        huff.encode_symbol(0x00, &mut bits);
+
+        let (symbol, len) = huff.decode_symbol(&bits[1..])?;
+        assert_eq!((symbol, len), (0x2A, symbol_len));
+
+        let (symbol, _) = huff.decode_symbol(&bits[1 + len + 1..])?;
+        assert_eq!(symbol, 0x00);
+
         Ok(())
     }
 }
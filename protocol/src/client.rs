@@ -1,10 +1,30 @@
 use super::{
-    ConnectionlessPacket, FragmentInfo, FragmentLength, FragmentStart,
-    InvalidConnectionlessPacketError, InvalidFragmentLengthError, InvalidFragmentStartError,
-    PacketKind, PacketSequenceNumber, QPort,
+    info_int, info_value, Challenge, ConnectionlessPacket, FragmentInfo, FragmentLength,
+    FragmentStart, InvalidConnectionlessPacketError, InvalidFragmentLengthError,
+    InvalidFragmentStartError, InvalidInfoValueError, NetchanChecksum, PacketKind, PacketSequence,
+    PacketSequenceNumber, ProtocolVersion, QPort, UnknownCommandError,
 };
-use crate::net::chan::FRAGMENT_SIZE;
-use bytes::{Buf, Bytes};
+use crate::cmd;
+use crate::net::chan::{FragmentQueue, Netchan, FRAGMENT_SIZE, MAX_PACKETLEN};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use quake3::info::{BigInfo, Info, InfoStr, ParseError};
+use quake3::qstr::{QStr, QString};
+use std::ffi::c_int;
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+use winnow::ascii::Caseless;
+use winnow::binary::be_u16;
+use winnow::combinator::{alt, delimited, not, opt, preceded, repeat, seq, terminated};
+use winnow::error::ContextError;
+use winnow::token::{literal, rest, take, take_while};
+use winnow::ModalResult;
+use winnow::Parser;
+
+// sequence and qport
+const HEADER_LEN: usize = core::mem::size_of::<i32>() + core::mem::size_of::<u16>();
+// optional ioq3 checksum
+const CHECKSUM_LEN: usize = core::mem::size_of::<i32>();
+// fragment start and length
+const FRAGMENT_HEADER_LEN: usize = 2 * core::mem::size_of::<u16>();
 
 #[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[error("is invalid")]
@@ -16,7 +36,7 @@ pub struct InvalidSequencedPacketError {
 pub struct SequencedPacket {
     sequence: PacketSequenceNumber,
     qport: QPort,
-    // TODO: ioq3 has additional checksum
+    checksum: Option<NetchanChecksum>,
     payload: Bytes,
 }
 
@@ -34,11 +54,25 @@ impl SequencedPacket {
             Ok(Self {
                 sequence,
                 qport,
+                checksum: None,
                 payload,
             })
         }
     }
 
+    /// Wrap `payload` into the next outgoing packet of `netchan` like `Netchan_Transmit`
+    pub fn transmit<T: Into<Bytes>>(
+        netchan: &mut Netchan,
+        payload: T,
+    ) -> Result<Self, InvalidSequencedPacketError> {
+        let payload: Bytes = payload.into();
+        if payload.len() >= FRAGMENT_SIZE {
+            return Err(InvalidSequencedPacketError { payload });
+        }
+        let packet = Self::new(netchan.transmit(), netchan.qport(), payload)?;
+        Ok(packet.with_checksum_opt(netchan.checksum_challenge()))
+    }
+
     pub fn sequence(&self) -> PacketSequenceNumber {
         self.sequence
     }
@@ -47,9 +81,41 @@ impl SequencedPacket {
         self.qport
     }
 
+    /// ioq3 checksum, only present for [`ProtocolVersion::IoQuake3`]
+    pub fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum
+    }
+
+    /// Add the ioq3 checksum for `challenge`
+    pub fn with_checksum(mut self, challenge: Challenge) -> Self {
+        self.checksum = Some(NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
+    /// Add the ioq3 checksum if there is a `challenge`, like [`Netchan::checksum_challenge`]
+    pub(crate) fn with_checksum_opt(mut self, challenge: Option<Challenge>) -> Self {
+        self.checksum = challenge.map(|challenge| NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
     pub fn payload(&self) -> &Bytes {
         &self.payload
     }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        PacketSequence::new_with_number_and_fragment(self.sequence, false).write_to(buf);
+        buf.put_u16_le(self.qport.0);
+        if let Some(checksum) = self.checksum {
+            checksum.write_to(buf);
+        }
+        buf.put_slice(&self.payload);
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(HEADER_LEN + CHECKSUM_LEN + self.payload.len());
+        self.write_to(&mut buf);
+        buf.freeze()
+    }
 }
 
 #[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -62,7 +128,7 @@ pub struct InvalidFragmentedPacketError {
 pub struct FragmentedPacket {
     sequence: PacketSequenceNumber,
     qport: QPort,
-    // TODO: ioq3 has additional checksum
+    checksum: Option<NetchanChecksum>,
     fragment_info: FragmentInfo,
     payload: Bytes,
 }
@@ -82,6 +148,7 @@ impl FragmentedPacket {
             Ok(fragment_length) => Ok(Self {
                 sequence,
                 qport,
+                checksum: None,
                 fragment_info: FragmentInfo::new(fragment_start, fragment_length),
                 payload,
             }),
@@ -104,9 +171,52 @@ impl FragmentedPacket {
         self.fragment_info.is_last()
     }
 
+    /// ioq3 checksum, only present for [`ProtocolVersion::IoQuake3`]
+    pub fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum
+    }
+
+    /// Add the ioq3 checksum for `challenge`
+    pub fn with_checksum(mut self, challenge: Challenge) -> Self {
+        self.checksum = Some(NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
+    /// Add the ioq3 checksum if there is a `challenge`, like [`Netchan::checksum_challenge`]
+    pub(crate) fn with_checksum_opt(mut self, challenge: Option<Challenge>) -> Self {
+        self.checksum = challenge.map(|challenge| NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
     pub fn payload(&self) -> &Bytes {
         &self.payload
     }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        PacketSequence::new_with_number_and_fragment(self.sequence, true).write_to(buf);
+        buf.put_u16_le(self.qport.0);
+        if let Some(checksum) = self.checksum {
+            checksum.write_to(buf);
+        }
+        self.fragment_info.write_to(buf);
+        buf.put_slice(&self.payload);
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(
+            HEADER_LEN + CHECKSUM_LEN + FRAGMENT_HEADER_LEN + self.payload.len(),
+        );
+        self.write_to(&mut buf);
+        buf.freeze()
+    }
+
+    /// Take the next fragment of `queue`, like `Netchan_TransmitNextFragment`
+    pub fn next_from(queue: &mut FragmentQueue, qport: QPort) -> Option<Self> {
+        let (start, payload) = queue.next_fragment()?;
+        let packet = Self::new(queue.sequence(), qport, start, payload)
+            .expect("fragment should be at most FRAGMENT_SIZE");
+        Some(packet.with_checksum_opt(queue.checksum_challenge()))
+    }
 }
 
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -124,12 +234,27 @@ pub enum InvalidServerPacketError {
     InvalidFragmentStart(#[from] InvalidFragmentStartError),
     InvalidFragmentLength(#[from] InvalidFragmentLengthError),
     InvalidFragmentedPacket(#[from] crate::server::InvalidFragmentedPacketError),
+
+    InvalidSize,
+    InvalidChecksum,
+}
+
+/// Parse incoming server packet of [`ProtocolVersion::Quake3`]
+pub fn parse_server_packet(payload: impl Buf) -> Result<ServerPacket, InvalidServerPacketError> {
+    parse_server_packet_with(payload, ProtocolVersion::Quake3, Challenge::default())
 }
 
-pub fn parse_server_packet(
+/// Parse incoming server packet of `protocol`
+///
+/// For [`ProtocolVersion::IoQuake3`] the checksum of sequenced packets is verified against `challenge`.
+pub fn parse_server_packet_with(
     mut payload: impl Buf,
+    protocol: ProtocolVersion,
+    challenge: Challenge,
 ) -> Result<ServerPacket, InvalidServerPacketError> {
-    // FIXME: this panics if payload doesn't have a next i32, unlike e.g. nom::Err::Incomplete
+    if payload.remaining() < core::mem::size_of::<i32>() {
+        return Err(InvalidServerPacketError::InvalidSize);
+    }
     let packet_kind = PacketKind::parse(payload.get_i32_le());
 
     let packet = match packet_kind {
@@ -139,24 +264,44 @@ pub fn parse_server_packet(
             ServerPacket::Connectionless(packet)
         }
         PacketKind::Sequenced(sequence) => {
+            let checksum_challenge = if protocol.has_checksum() {
+                if payload.remaining() < core::mem::size_of::<i32>() {
+                    return Err(InvalidServerPacketError::InvalidSize);
+                }
+                let checksum = NetchanChecksum(payload.get_i32_le());
+                if checksum != NetchanChecksum::new(challenge, sequence.number()) {
+                    return Err(InvalidServerPacketError::InvalidChecksum);
+                }
+                Some(challenge)
+            } else {
+                None
+            };
+
             if sequence.is_fragmented() {
-                // FIXME: this panics if payload doesn't have a next u16, unlike e.g. nom::Err::Incomplete
+                if payload.remaining() < core::mem::size_of::<u16>() {
+                    return Err(InvalidServerPacketError::InvalidSize);
+                }
                 let fragment_start = FragmentStart::new(payload.get_u16_le())?;
-                // FIXME: this panics if payload doesn't have a next u16, unlike e.g. nom::Err::Incomplete
+                if payload.remaining() < core::mem::size_of::<u16>() {
+                    return Err(InvalidServerPacketError::InvalidSize);
+                }
                 let fragment_length = FragmentLength::new(payload.get_u16_le())?;
                 let fragment_info = FragmentInfo::new(fragment_start, fragment_length);
-                // TODO: this should be an error, not a panic
-                assert_eq!(usize::from(fragment_info.length()), payload.remaining());
+                if usize::from(fragment_info.length()) != payload.remaining() {
+                    return Err(InvalidServerPacketError::InvalidSize);
+                }
                 let payload = payload.copy_to_bytes(payload.remaining());
                 let packet = crate::server::FragmentedPacket::new(
                     sequence.number(),
                     fragment_info.start(),
                     payload,
-                )?;
+                )?
+                .with_checksum_opt(checksum_challenge);
                 ServerPacket::Fragmented(packet)
             } else {
                 let payload = payload.copy_to_bytes(payload.remaining());
-                let packet = crate::server::SequencedPacket::new(sequence.number(), payload)?;
+                let packet = crate::server::SequencedPacket::new(sequence.number(), payload)?
+                    .with_checksum_opt(checksum_challenge);
                 ServerPacket::Sequenced(packet)
             }
         }
@@ -165,48 +310,1890 @@ pub fn parse_server_packet(
     Ok(packet)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// `challengeResponse` server message
+pub const CHALLENGERESPONSE_COMMAND: &[u8] = b"challengeResponse";
 
-    #[test]
-    fn sequencedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
-        assert!(SequencedPacket::new(
-            PacketSequenceNumber::new(42)?,
-            QPort::new(27960)?,
-            vec![0; FRAGMENT_SIZE]
-        )
-        .is_err());
+/// Connectionless incoming `challengeResponse` server message
+///
+/// id Quake 3 only sends the challenge, ioq3 adds the client challenge from [`crate::server::GetChallengeMessage`] and its protocol.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ChallengeResponseMessage {
+    challenge: Challenge,
+    client_challenge: Option<Challenge>,
+    protocol: Option<c_int>,
+}
 
-        assert!(SequencedPacket::new(
-            PacketSequenceNumber::new(42)?,
-            QPort::new(27960)?,
-            vec![0; FRAGMENT_SIZE - 1]
-        )
-        .is_ok());
+/// Parse error for [`ChallengeResponseMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseChallengeResponseMessageError(());
 
-        Ok(())
+impl ChallengeResponseMessage {
+    pub fn new(challenge: Challenge) -> Self {
+        Self {
+            challenge,
+            client_challenge: None,
+            protocol: None,
+        }
     }
 
-    #[test]
-    fn fragmentedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
-        assert!(FragmentedPacket::new(
-            PacketSequenceNumber::new(42)?,
-            QPort::new(27960)?,
-            FragmentStart::new(42)?,
-            vec![0; FRAGMENT_SIZE + 1]
-        )
-        .is_err());
+    pub fn with_client_challenge(mut self, client_challenge: Challenge) -> Self {
+        self.client_challenge = Some(client_challenge);
+        self
+    }
 
-        assert!(FragmentedPacket::new(
-            PacketSequenceNumber::new(42)?,
-            QPort::new(27960)?,
-            FragmentStart::new(42)?,
-            vec![0; FRAGMENT_SIZE]
-        )
-        .is_ok());
+    /// Protocol of the server, e.g. [`ProtocolVersion::number`]
+    pub fn with_protocol(mut self, protocol: c_int) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
 
-        Ok(())
+    /// Challenge of the server, which the client sends back in `connect`
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+
+    pub fn client_challenge(&self) -> Option<Challenge> {
+        self.client_challenge
+    }
+
+    pub fn protocol(&self) -> Option<c_int> {
+        self.protocol
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<ChallengeResponseMessage, ParseChallengeResponseMessageError> {
+        const ERROR: ParseChallengeResponseMessageError = ParseChallengeResponseMessageError(());
+
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), CHALLENGERESPONSE_COMMAND).ok_or(ERROR)?;
+        let mut int_argument = || {
+            arguments
+                .next()
+                .map(|argument| cmd::int_argument.parse(argument).map_err(|_e| ERROR))
+                .transpose()
+        };
+
+        let challenge = int_argument()?.ok_or(ERROR)?;
+        let mut message = ChallengeResponseMessage::new(Challenge::new(challenge));
+        if let Some(client_challenge) = int_argument()? {
+            message = message.with_client_challenge(Challenge::new(client_challenge));
+        }
+        if let Some(protocol) = int_argument()? {
+            message = message.with_protocol(protocol);
+        }
+        Ok(message)
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(CHALLENGERESPONSE_COMMAND);
+        cmd::write_int_argument(buf, self.challenge.into());
+        // the protocol is the third argument, so it needs a client challenge before it
+        if self.client_challenge.is_some() || self.protocol.is_some() {
+            cmd::write_int_argument(buf, self.client_challenge.unwrap_or_default().into());
+        }
+        if let Some(protocol) = self.protocol {
+            cmd::write_int_argument(buf, protocol);
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `connectResponse` server message
+pub const CONNECTRESPONSE_COMMAND: &[u8] = b"connectResponse";
+
+/// Connectionless incoming `connectResponse` server message
+///
+/// This accepts [`crate::server::ConnectMessage`], further packets are sequenced.
+/// id Quake 3 sends no arguments, ioq3 adds the challenge for `CL_ConnectionlessPacket` to check.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ConnectResponseMessage {
+    challenge: Option<Challenge>,
+}
+
+/// Parse error for [`ConnectResponseMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseConnectResponseMessageError(());
+
+impl ConnectResponseMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_challenge(mut self, challenge: Challenge) -> Self {
+        self.challenge = Some(challenge);
+        self
+    }
+
+    pub fn challenge(&self) -> Option<Challenge> {
+        self.challenge
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<ConnectResponseMessage, ParseConnectResponseMessageError> {
+        const ERROR: ParseConnectResponseMessageError = ParseConnectResponseMessageError(());
+
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), CONNECTRESPONSE_COMMAND).ok_or(ERROR)?;
+        let challenge = arguments
+            .next()
+            .map(|challenge| cmd::int_argument.parse(challenge).map_err(|_e| ERROR))
+            .transpose()?;
+        Ok(ConnectResponseMessage {
+            challenge: challenge.map(Challenge::new),
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(CONNECTRESPONSE_COMMAND);
+        if let Some(challenge) = self.challenge {
+            cmd::write_int_argument(buf, challenge.into());
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `print` server message
+pub const PRINT_COMMAND: &[u8] = b"print";
+
+// SV_OUTPUTBUF_LENGTH without the terminating NUL
+const MAX_PRINT_LEN: usize = 1024 - 16 - 1;
+
+/// Connectionless incoming `print` server message
+///
+/// Game servers send console output like rejected connections or [`crate::server::RconMessage`] replies with this.
+/// Long output is split across several packets, which [`RconOutput`] joins again.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct PrintMessage {
+    text: QString,
+}
+
+/// Parse error for [`PrintMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParsePrintMessageError(());
+
+impl PrintMessage {
+    pub fn new(text: QString) -> Self {
+        Self { text }
+    }
+
+    /// Split `text` into messages like the redirected output buffer of `SV_FlushRedirect`
+    pub fn split(text: &QStr) -> Vec<Self> {
+        let bytes = text.as_bytes();
+        if bytes.is_empty() {
+            return vec![Self::new(text.to_owned())];
+        }
+        bytes
+            .chunks(MAX_PRINT_LEN)
+            .map(|chunk| {
+                Self::new(QString::from_bytes(chunk).expect("text should not contain NUL"))
+            })
+            .collect()
+    }
+
+    pub fn text(&self) -> &QStr {
+        &self.text
+    }
+
+    pub fn into_text(self) -> QString {
+        self.text
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<PrintMessage, ParsePrintMessageError> {
+        const ERROR: ParsePrintMessageError = ParsePrintMessageError(());
+
+        let payload: &[u8] = packet.payload();
+        cmd::command_arguments(payload, PRINT_COMMAND).ok_or(ERROR)?;
+        // the text is read with MSG_ReadString after the command line
+        let end = payload.iter().position(|&c| c == b'\n').ok_or(ERROR)?;
+        let text = &payload[end + 1..];
+        let text = QString::from_bytes(text).map_err(|_e| ERROR)?;
+        Ok(PrintMessage { text })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(PRINT_COMMAND);
+        buf.put_u8(b'\n');
+        buf.put_slice(self.text.as_bytes());
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// Output of a [`crate::server::RconMessage`] collected from several [`PrintMessage`]
+///
+/// There is no marker for the last packet, so callers stop collecting after a timeout.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct RconOutput {
+    text: Vec<u8>,
+}
+
+impl RconOutput {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append the text of `message`
+    pub fn push(&mut self, message: PrintMessage) {
+        self.text.extend_from_slice(message.text.as_bytes());
+    }
+
+    pub fn text(&self) -> &QStr {
+        QStr::from_bytes(&self.text).expect("text should not contain NUL")
+    }
+
+    pub fn into_text(self) -> QString {
+        QString::from_bytes(self.text).expect("text should not contain NUL")
+    }
+}
+
+/// `echo` server message
+pub const ECHO_COMMAND: &[u8] = b"echo";
+
+/// Error for [`EchoMessage`] text that can not be quoted
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidEchoMessageError(());
+
+/// Connectionless incoming `echo` server message
+///
+/// The game client replies with the bare text in `CL_ConnectionlessPacket`, see [`EchoMessage::reply_packet`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct EchoMessage {
+    text: QString,
+}
+
+/// Parse error for [`EchoMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseEchoMessageError(());
+
+impl EchoMessage {
+    /// The text is written as one quoted argument, so it must not contain `"`
+    pub fn new(text: QString) -> Result<Self, InvalidEchoMessageError> {
+        if text.as_bytes().contains(&b'"') {
+            Err(InvalidEchoMessageError(()))
+        } else {
+            Ok(Self { text })
+        }
+    }
+
+    pub fn text(&self) -> &QStr {
+        &self.text
+    }
+
+    /// Only the first argument is echoed, like `Cmd_Argv(1)`
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<EchoMessage, ParseEchoMessageError> {
+        let payload: &[u8] = packet.payload();
+        let (text,) =
+            seq!(_: literal(Caseless(ECHO_COMMAND)), opt((cmd::separator, rest::<_, ContextError>)))
+                .parse(payload)
+                .map_err(|_e| ParseEchoMessageError(()))?;
+        let text = text
+            .and_then(|(_, arguments)| cmd::tokenize(arguments).first().copied())
+            .unwrap_or_default();
+        let text = QString::from_bytes(text).map_err(|_e| ParseEchoMessageError(()))?;
+        Ok(EchoMessage { text })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(ECHO_COMMAND);
+        buf.put_slice(b" \"");
+        buf.put_slice(self.text.as_bytes());
+        buf.put_u8(b'"');
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+
+    /// Reply of the game client, which is the text without any command
+    pub fn reply_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        ConnectionlessPacket::new(Bytes::copy_from_slice(self.text.as_bytes()))
+    }
+}
+
+// `disconnect` is the same message in both directions
+pub use crate::server::{DisconnectMessage, ParseDisconnectMessageError, DISCONNECT_COMMAND};
+
+/// `keyAuthorize` auth server message
+pub const KEYAUTHORIZE_COMMAND: &[u8] = b"keyAuthorize";
+
+/// Connectionless incoming `keyAuthorize` auth server message
+///
+/// `CL_ConnectionlessPacket` ignores this, so the arguments are kept as they are.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct KeyAuthorizeMessage {
+    arguments: QString,
+}
+
+/// Parse error for [`KeyAuthorizeMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseKeyAuthorizeMessageError(());
+
+impl KeyAuthorizeMessage {
+    pub fn new(arguments: QString) -> Self {
+        Self { arguments }
+    }
+
+    pub fn arguments(&self) -> &QStr {
+        &self.arguments
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<KeyAuthorizeMessage, ParseKeyAuthorizeMessageError> {
+        const ERROR: ParseKeyAuthorizeMessageError = ParseKeyAuthorizeMessageError(());
+
+        let arguments =
+            cmd::command_arguments(packet.payload(), KEYAUTHORIZE_COMMAND).ok_or(ERROR)?;
+        let arguments = arguments.rest();
+        let arguments = &arguments[arguments.iter().take_while(|&&c| c == b' ').count()..];
+        let arguments = QString::from_bytes(arguments).map_err(|_e| ERROR)?;
+        Ok(KeyAuthorizeMessage { arguments })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(KEYAUTHORIZE_COMMAND);
+        if !self.arguments.as_bytes().is_empty() {
+            buf.put_u8(b' ');
+            buf.put_slice(self.arguments.as_bytes());
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `motd` update server message
+pub const MOTD_COMMAND: &[u8] = b"motd";
+
+/// Error for [`MotdMessage`] info that can not be quoted
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidMotdMessageError(());
+
+/// Connectionless incoming `motd` update server message
+///
+/// This is the reply to `getmotd` with a quoted info, which `CL_MotdPacket` reads.
+pub struct MotdMessage {
+    info: Info,
+    skipped: Vec<ParseError>,
+}
+
+/// Parse error for [`MotdMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseMotdMessageError(());
+
+impl MotdMessage {
+    /// The info is written as one quoted argument, so it must not contain `"`
+    pub fn new(info: Info) -> Result<Self, InvalidMotdMessageError> {
+        if info
+            .iter()
+            .any(|(k, v)| k.as_bytes().contains(&b'"') || v.as_bytes().contains(&b'"'))
+        {
+            Err(InvalidMotdMessageError(()))
+        } else {
+            Ok(Self {
+                info,
+                skipped: Vec::new(),
+            })
+        }
+    }
+
+    pub fn info(&self) -> &Info {
+        &self.info
+    }
+
+    pub fn into_info(self) -> Info {
+        self.info
+    }
+
+    /// Info entries that were skipped while parsing, see [`Info::parse_lenient`]
+    pub fn skipped(&self) -> &[ParseError] {
+        &self.skipped
+    }
+
+    /// Echoed challenge of `getmotd`, i.e. `challenge`
+    pub fn challenge(&self) -> Option<&InfoStr> {
+        info_value(&self.info, "challenge")
+    }
+
+    /// Message of the day, i.e. `motd`
+    pub fn motd(&self) -> Option<&InfoStr> {
+        info_value(&self.info, "motd")
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<MotdMessage, ParseMotdMessageError> {
+        const ERROR: ParseMotdMessageError = ParseMotdMessageError(());
+
+        let mut arguments = cmd::Tokens::new(packet.payload());
+        if !arguments
+            .next()
+            .is_some_and(|command| command.eq_ignore_ascii_case(MOTD_COMMAND))
+        {
+            return Err(ERROR);
+        }
+        let (info, skipped) = Info::parse_lenient(arguments.next().unwrap_or_default());
+        Ok(MotdMessage { info, skipped })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(MOTD_COMMAND);
+        buf.put_slice(b" \"");
+        self.info.write_to(buf);
+        buf.put_u8(b'"');
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `infoResponse` server message
+pub const INFORESPONSE_COMMAND: &[u8] = b"infoResponse";
+
+/// Connectionless incoming `infoResponse` server message
+///
+/// This is the reply to [`crate::server::GetInfoMessage`] with a short serverinfo, built in `SVC_Info`.
+pub struct InfoResponseMessage {
+    info: Info,
+    skipped: Vec<ParseError>,
+}
+
+/// Parse error for [`InfoResponseMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseInfoResponseMessageError(());
+
+impl InfoResponseMessage {
+    pub fn new(info: Info) -> Self {
+        Self {
+            info,
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn info(&self) -> &Info {
+        &self.info
+    }
+
+    pub fn into_info(self) -> Info {
+        self.info
+    }
+
+    /// Info entries that were skipped while parsing, see [`Info::parse_lenient`]
+    pub fn skipped(&self) -> &[ParseError] {
+        &self.skipped
+    }
+
+    /// Echoed challenge of [`crate::server::GetInfoMessage`], i.e. `challenge`
+    pub fn challenge(&self) -> Option<&InfoStr> {
+        info_value(&self.info, "challenge")
+    }
+
+    /// Protocol of the game server, i.e. `protocol`
+    pub fn protocol(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        info_int(&self.info, "protocol")
+    }
+
+    /// Name of the game server, i.e. `hostname`
+    pub fn hostname(&self) -> Option<&InfoStr> {
+        info_value(&self.info, "hostname")
+    }
+
+    /// Name of the current map, i.e. `mapname`
+    pub fn mapname(&self) -> Option<&InfoStr> {
+        info_value(&self.info, "mapname")
+    }
+
+    /// Number of connected game clients including bots, i.e. `clients`
+    pub fn clients(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        info_int(&self.info, "clients")
+    }
+
+    /// Number of public slots, i.e. `sv_maxclients`
+    pub fn max_clients(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        info_int(&self.info, "sv_maxclients")
+    }
+
+    /// Game type of the game server, i.e. `gametype`
+    pub fn gametype(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        info_int(&self.info, "gametype")
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<InfoResponseMessage, ParseInfoResponseMessageError> {
+        let payload: &[u8] = packet.payload();
+        let (info,) =
+            seq!(_: literal(Caseless(INFORESPONSE_COMMAND)), _: literal(b"\n"), rest::<_, ContextError>)
+                .parse(payload)
+                .map_err(|_e| ParseInfoResponseMessageError(()))?;
+        let (info, skipped) = Info::parse_lenient(info);
+        Ok(InfoResponseMessage { info, skipped })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(INFORESPONSE_COMMAND);
+        buf.put_u8(b'\n');
+        self.info.write_to(buf);
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `statusResponse` server message
+pub const STATUSRESPONSE_COMMAND: &[u8] = b"statusResponse";
+
+/// Error for invalid [`PlayerStatus`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidPlayerStatusError(());
+
+/// Player line of [`StatusResponseMessage`]
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct PlayerStatus {
+    score: c_int,
+    ping: c_int,
+    name: QString,
+}
+
+impl PlayerStatus {
+    /// The name is quoted and on a line of its own, so it can neither contain `"` nor a newline
+    pub fn new(score: c_int, ping: c_int, name: QString) -> Result<Self, InvalidPlayerStatusError> {
+        if name.as_bytes().iter().any(|&c| c == b'"' || c == b'\n') {
+            Err(InvalidPlayerStatusError(()))
+        } else {
+            Ok(Self { score, ping, name })
+        }
+    }
+
+    /// Score of the player, i.e. `PERS_SCORE`
+    pub fn score(&self) -> c_int {
+        self.score
+    }
+
+    pub fn ping(&self) -> c_int {
+        self.ping
+    }
+
+    /// Name of the player, including color codes
+    pub fn name(&self) -> &QStr {
+        &self.name
+    }
+}
+
+fn parse_playerstatus(input: &mut &[u8]) -> ModalResult<PlayerStatus> {
+    let (score, ping, name) = terminated(
+        (
+            cmd::int_argument,
+            preceded(literal(b" "), cmd::int_argument),
+            preceded(
+                literal(b" "),
+                delimited(
+                    literal(b"\""),
+                    take_while(0.., |c: u8| c != b'"' && c != b'\n' && c != b'\0'),
+                    literal(b"\""),
+                ),
+            ),
+        ),
+        literal(b"\n"),
+    )
+    .parse_next(input)?;
+
+    let name = QString::from_bytes(name).expect("name should not contain NUL");
+    Ok(PlayerStatus { score, ping, name })
+}
+
+/// Connectionless incoming `statusResponse` server message
+///
+/// This is the reply to [`crate::server::GetStatusMessage`] with the whole serverinfo and one line per player, built in `SVC_Status`.
+pub struct StatusResponseMessage {
+    info: BigInfo,
+    players: Vec<PlayerStatus>,
+    skipped: Vec<ParseError>,
+}
+
+/// Parse error for [`StatusResponseMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseStatusResponseMessageError(());
+
+impl StatusResponseMessage {
+    pub fn new(info: BigInfo, players: Vec<PlayerStatus>) -> Self {
+        Self {
+            info,
+            players,
+            skipped: Vec::new(),
+        }
+    }
+
+    pub fn info(&self) -> &BigInfo {
+        &self.info
+    }
+
+    pub fn players(&self) -> &[PlayerStatus] {
+        &self.players
+    }
+
+    pub fn into_parts(self) -> (BigInfo, Vec<PlayerStatus>) {
+        (self.info, self.players)
+    }
+
+    /// Info entries that were skipped while parsing, see [`BigInfo::parse_lenient`]
+    pub fn skipped(&self) -> &[ParseError] {
+        &self.skipped
+    }
+
+    /// Echoed challenge of [`crate::server::GetStatusMessage`], i.e. `challenge`
+    pub fn challenge(&self) -> Option<&InfoStr> {
+        info_value(&self.info, "challenge")
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<StatusResponseMessage, ParseStatusResponseMessageError> {
+        let payload: &[u8] = packet.payload();
+        let (info, players) = seq!(
+            _: literal(Caseless(STATUSRESPONSE_COMMAND)),
+            _: literal(b"\n"),
+            take_while(0.., |c: u8| c != b'\n'),
+            _: literal(b"\n"),
+            repeat(0.., parse_playerstatus),
+        )
+        .parse(payload)
+        .map_err(|_e| ParseStatusResponseMessageError(()))?;
+        let (info, skipped) = BigInfo::parse_lenient(info);
+        Ok(StatusResponseMessage {
+            info,
+            players,
+            skipped,
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(STATUSRESPONSE_COMMAND);
+        buf.put_u8(b'\n');
+        self.info.write_to(buf);
+        buf.put_u8(b'\n');
+        for player in &self.players {
+            buf.put_slice(player.score.to_string().as_bytes());
+            cmd::write_int_argument(buf, player.ping);
+            buf.put_slice(b" \"");
+            buf.put_slice(player.name.as_bytes());
+            buf.put_slice(b"\"\n");
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `getserversResponse` master server message
+pub const GETSERVERSRESPONSE_COMMAND: &[u8] = b"getserversResponse";
+
+// marks the last packet of a response
+const EOT: &[u8] = b"\\EOT\0\0\0";
+
+// `CL_ServersResponsePacket` stops at a record too short for an address, so the padding is optional
+fn parse_eot(input: &mut &[u8]) -> ModalResult<()> {
+    alt((literal(EOT), literal(&EOT[..4])))
+        .void()
+        .parse_next(input)
+}
+
+// `\\`, address and port
+const IPV4_RECORD_LEN: usize = 1 + 4 + 2;
+
+/// Maximum number of game servers per packet, see [`GetServersResponseMessage::split`]
+pub const MAX_GETSERVERSRESPONSE_SERVERS: usize =
+    (MAX_PACKETLEN - core::mem::size_of::<c_int>() - GETSERVERSRESPONSE_COMMAND.len() - EOT.len())
+        / IPV4_RECORD_LEN;
+
+fn parse_ipv4_record(input: &mut &[u8]) -> ModalResult<SocketAddrV4> {
+    let (ip, port) = preceded(literal(b"\\"), (take(4usize), be_u16)).parse_next(input)?;
+    let ip = Ipv4Addr::new(ip[0], ip[1], ip[2], ip[3]);
+    Ok(SocketAddrV4::new(ip, port))
+}
+
+fn write_ipv4_record(buf: &mut impl BufMut, server: &SocketAddrV4) {
+    buf.put_u8(b'\\');
+    buf.put_slice(&server.ip().octets());
+    buf.put_u16(server.port());
+}
+
+// `/`, address and port
+const IPV6_RECORD_LEN: usize = 1 + 16 + 2;
+
+fn parse_ipv6_record(input: &mut &[u8]) -> ModalResult<SocketAddrV6> {
+    let (ip, port) = preceded(literal(b"/"), (take(16usize), be_u16)).parse_next(input)?;
+    let ip: [u8; 16] = ip.try_into().expect("address should be 16 bytes");
+    Ok(SocketAddrV6::new(Ipv6Addr::from(ip), port, 0, 0))
+}
+
+fn write_ipv6_record(buf: &mut impl BufMut, server: &SocketAddrV6) {
+    buf.put_u8(b'/');
+    buf.put_slice(&server.ip().octets());
+    buf.put_u16(server.port());
+}
+
+/// Connectionless incoming `getserversResponse` master server message
+///
+/// This is the reply to [`crate::server::GetServersMessage`].
+/// Long lists are split across several packets, and only the last one ends with `\\EOT`.
+/// Use [`ServerList`] to collect them.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetServersResponseMessage {
+    servers: Vec<SocketAddrV4>,
+    last: bool,
+}
+
+/// Parse error for [`GetServersResponseMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseGetServersResponseMessageError(());
+
+impl GetServersResponseMessage {
+    pub fn new(servers: Vec<SocketAddrV4>, last: bool) -> Self {
+        Self { servers, last }
+    }
+
+    /// Split `servers` into messages that each fit into a [`ConnectionlessPacket`], like a master server would
+    pub fn split(servers: &[SocketAddrV4]) -> Vec<Self> {
+        let mut messages: Vec<_> = servers
+            .chunks(MAX_GETSERVERSRESPONSE_SERVERS)
+            .map(|servers| Self::new(servers.to_vec(), false))
+            .collect();
+        match messages.last_mut() {
+            Some(last) => last.last = true,
+            None => messages.push(Self::new(Vec::new(), true)),
+        }
+        messages
+    }
+
+    pub fn servers(&self) -> &[SocketAddrV4] {
+        &self.servers
+    }
+
+    /// Whether this is the last packet of the response, i.e. it ends with `\\EOT`
+    pub fn is_last(&self) -> bool {
+        self.last
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<GetServersResponseMessage, ParseGetServersResponseMessageError> {
+        let payload: &[u8] = packet.payload();
+        let (servers, last) = seq!(
+            _: literal(GETSERVERSRESPONSE_COMMAND),
+            // an EOT record would also be a valid address
+            repeat(0.., preceded(not(literal(EOT)), parse_ipv4_record)),
+            opt(parse_eot),
+        )
+        .parse(payload)
+        .map_err(|_e| ParseGetServersResponseMessageError(()))?;
+        Ok(GetServersResponseMessage {
+            servers,
+            last: last.is_some(),
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETSERVERSRESPONSE_COMMAND);
+        for server in &self.servers {
+            write_ipv4_record(buf, server);
+        }
+        if self.last {
+            buf.put_slice(EOT);
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `getserversExtResponse` master server message
+pub const GETSERVERSEXTRESPONSE_COMMAND: &[u8] = b"getserversExtResponse";
+
+/// Connectionless incoming `getserversExtResponse` master server message
+///
+/// This is the reply to [`crate::server::GetServersExtMessage`], with both IPv4 and IPv6 game servers.
+/// Like [`GetServersResponseMessage`] long lists are split across several packets.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetServersExtResponseMessage {
+    servers: Vec<SocketAddr>,
+    last: bool,
+}
+
+/// Parse error for [`GetServersExtResponseMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseGetServersExtResponseMessageError(());
+
+impl GetServersExtResponseMessage {
+    pub fn new(servers: Vec<SocketAddr>, last: bool) -> Self {
+        Self { servers, last }
+    }
+
+    /// Split `servers` into messages that each fit into a [`ConnectionlessPacket`], like a master server would
+    pub fn split(servers: &[SocketAddr]) -> Vec<Self> {
+        const BUDGET: usize = MAX_PACKETLEN
+            - core::mem::size_of::<c_int>()
+            - GETSERVERSEXTRESPONSE_COMMAND.len()
+            - EOT.len();
+
+        let mut messages = vec![Self::default()];
+        let mut len = 0;
+        for server in servers {
+            let record_len = match server {
+                SocketAddr::V4(_) => IPV4_RECORD_LEN,
+                SocketAddr::V6(_) => IPV6_RECORD_LEN,
+            };
+            if len + record_len > BUDGET {
+                messages.push(Self::default());
+                len = 0;
+            }
+            len += record_len;
+            messages
+                .last_mut()
+                .expect("there should be a message")
+                .servers
+                .push(*server);
+        }
+        messages.last_mut().expect("there should be a message").last = true;
+        messages
+    }
+
+    pub fn servers(&self) -> &[SocketAddr] {
+        &self.servers
+    }
+
+    /// Whether this is the last packet of the response, i.e. it ends with `\\EOT`
+    pub fn is_last(&self) -> bool {
+        self.last
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<GetServersExtResponseMessage, ParseGetServersExtResponseMessageError> {
+        let payload: &[u8] = packet.payload();
+        let (servers, last) = seq!(
+            _: literal(GETSERVERSEXTRESPONSE_COMMAND),
+            repeat(
+                0..,
+                preceded(
+                    not(literal(EOT)),
+                    alt((
+                        parse_ipv4_record.map(SocketAddr::V4),
+                        parse_ipv6_record.map(SocketAddr::V6),
+                    )),
+                ),
+            ),
+            opt(parse_eot),
+        )
+        .parse(payload)
+        .map_err(|_e| ParseGetServersExtResponseMessageError(()))?;
+        Ok(GetServersExtResponseMessage {
+            servers,
+            last: last.is_some(),
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETSERVERSEXTRESPONSE_COMMAND);
+        for server in &self.servers {
+            match server {
+                SocketAddr::V4(server) => write_ipv4_record(buf, server),
+                SocketAddr::V6(server) => write_ipv6_record(buf, server),
+            }
+        }
+        if self.last {
+            buf.put_slice(EOT);
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// Game servers collected from several [`GetServersResponseMessage`] or [`GetServersExtResponseMessage`], like `CL_ServersResponsePacket`
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ServerList {
+    servers: Vec<SocketAddr>,
+    complete: bool,
+}
+
+impl ServerList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the servers of `message`, returns whether the list is complete
+    pub fn push(&mut self, message: GetServersResponseMessage) -> bool {
+        self.servers
+            .extend(message.servers.into_iter().map(SocketAddr::V4));
+        self.complete |= message.last;
+        self.complete
+    }
+
+    /// Add the servers of `message`, returns whether the list is complete
+    pub fn push_ext(&mut self, message: GetServersExtResponseMessage) -> bool {
+        self.servers.extend(message.servers);
+        self.complete |= message.last;
+        self.complete
+    }
+
+    pub fn servers(&self) -> &[SocketAddr] {
+        &self.servers
+    }
+
+    /// Whether the last packet has been received
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn into_servers(self) -> Vec<SocketAddr> {
+        self.servers
+    }
+}
+
+/// Kind of [`ServerConnectionlessMessage`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ServerConnectionlessCommand {
+    ChallengeResponse,
+    ConnectResponse,
+    InfoResponse,
+    StatusResponse,
+    Echo,
+    Print,
+    KeyAuthorize,
+    Motd,
+    GetServersResponse,
+    GetServersExtResponse,
+    Disconnect,
+}
+
+/// Parse error for [`ServerConnectionlessCommand`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct ParseServerCommandError(());
+
+impl ServerConnectionlessCommand {
+    const COMMANDS: [(&'static [u8], Self); 11] = [
+        (CHALLENGERESPONSE_COMMAND, Self::ChallengeResponse),
+        (CONNECTRESPONSE_COMMAND, Self::ConnectResponse),
+        (INFORESPONSE_COMMAND, Self::InfoResponse),
+        (STATUSRESPONSE_COMMAND, Self::StatusResponse),
+        (ECHO_COMMAND, Self::Echo),
+        (PRINT_COMMAND, Self::Print),
+        (KEYAUTHORIZE_COMMAND, Self::KeyAuthorize),
+        (MOTD_COMMAND, Self::Motd),
+        (GETSERVERSRESPONSE_COMMAND, Self::GetServersResponse),
+        (GETSERVERSEXTRESPONSE_COMMAND, Self::GetServersExtResponse),
+        (DISCONNECT_COMMAND, Self::Disconnect),
+    ];
+
+    /// Case insensitive like `CL_ConnectionlessPacket`
+    pub fn parse(bytes: &[u8]) -> Result<Self, ParseServerCommandError> {
+        Self::COMMANDS
+            .iter()
+            .find(|(command, _)| command.eq_ignore_ascii_case(bytes))
+            .map(|&(_, kind)| kind)
+            .ok_or(ParseServerCommandError(()))
+    }
+
+    pub fn as_bytes(&self) -> &'static [u8] {
+        Self::COMMANDS
+            .iter()
+            .find(|(_, kind)| kind == self)
+            .map(|&(command, _)| command)
+            .expect("all commands should be listed")
+    }
+}
+
+/// Connectionless incoming [`ServerPacket`] from game servers, master servers, the auth server or the update server
+pub enum ServerConnectionlessMessage {
+    ChallengeResponse(ChallengeResponseMessage),
+    ConnectResponse(ConnectResponseMessage),
+    InfoResponse(InfoResponseMessage),
+    StatusResponse(StatusResponseMessage),
+    Echo(EchoMessage),
+    Print(PrintMessage),
+    KeyAuthorize(KeyAuthorizeMessage),
+    Motd(MotdMessage),
+    GetServersResponse(GetServersResponseMessage),
+    GetServersExtResponse(GetServersExtResponseMessage),
+    Disconnect(DisconnectMessage),
+}
+
+/// Parse error for [`ServerConnectionlessMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ParseServerConnectionlessMessageError {
+    #[error("unknown command")]
+    UnknownCommand(#[from] UnknownCommandError),
+    #[error("invalid challengeResponse message")]
+    ChallengeResponse(#[from] ParseChallengeResponseMessageError),
+    #[error("invalid connectResponse message")]
+    ConnectResponse(#[from] ParseConnectResponseMessageError),
+    #[error("invalid infoResponse message")]
+    InfoResponse(#[from] ParseInfoResponseMessageError),
+    #[error("invalid statusResponse message")]
+    StatusResponse(#[from] ParseStatusResponseMessageError),
+    #[error("invalid echo message")]
+    Echo(#[from] ParseEchoMessageError),
+    #[error("invalid print message")]
+    Print(#[from] ParsePrintMessageError),
+    #[error("invalid keyAuthorize message")]
+    KeyAuthorize(#[from] ParseKeyAuthorizeMessageError),
+    #[error("invalid motd message")]
+    Motd(#[from] ParseMotdMessageError),
+    #[error("invalid getserversResponse message")]
+    GetServersResponse(#[from] ParseGetServersResponseMessageError),
+    #[error("invalid getserversExtResponse message")]
+    GetServersExtResponse(#[from] ParseGetServersExtResponseMessageError),
+    #[error("invalid disconnect message")]
+    Disconnect(#[from] ParseDisconnectMessageError),
+}
+
+impl ServerConnectionlessMessage {
+    pub fn command(&self) -> ServerConnectionlessCommand {
+        match self {
+            Self::ChallengeResponse(_) => ServerConnectionlessCommand::ChallengeResponse,
+            Self::ConnectResponse(_) => ServerConnectionlessCommand::ConnectResponse,
+            Self::InfoResponse(_) => ServerConnectionlessCommand::InfoResponse,
+            Self::StatusResponse(_) => ServerConnectionlessCommand::StatusResponse,
+            Self::Echo(_) => ServerConnectionlessCommand::Echo,
+            Self::Print(_) => ServerConnectionlessCommand::Print,
+            Self::KeyAuthorize(_) => ServerConnectionlessCommand::KeyAuthorize,
+            Self::Motd(_) => ServerConnectionlessCommand::Motd,
+            Self::GetServersResponse(_) => ServerConnectionlessCommand::GetServersResponse,
+            Self::GetServersExtResponse(_) => ServerConnectionlessCommand::GetServersExtResponse,
+            Self::Disconnect(_) => ServerConnectionlessCommand::Disconnect,
+        }
+    }
+
+    /// Parse the message by its first argument like `CL_ConnectionlessPacket`
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<ServerConnectionlessMessage, ParseServerConnectionlessMessageError> {
+        let payload: &[u8] = packet.payload();
+        // the binary server list follows right after the command, which is matched case sensitive
+        let command = if payload.starts_with(GETSERVERSRESPONSE_COMMAND) {
+            ServerConnectionlessCommand::GetServersResponse
+        } else if payload.starts_with(GETSERVERSEXTRESPONSE_COMMAND) {
+            ServerConnectionlessCommand::GetServersExtResponse
+        } else {
+            let command = cmd::Tokens::new(payload).next().unwrap_or_default();
+            ServerConnectionlessCommand::parse(command)
+                .map_err(|_e| UnknownCommandError::new(command))?
+        };
+
+        let message = match command {
+            ServerConnectionlessCommand::ChallengeResponse => {
+                Self::ChallengeResponse(ChallengeResponseMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::ConnectResponse => {
+                Self::ConnectResponse(ConnectResponseMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::InfoResponse => {
+                Self::InfoResponse(InfoResponseMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::StatusResponse => {
+                Self::StatusResponse(StatusResponseMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::Echo => Self::Echo(EchoMessage::parse_packet(packet)?),
+            ServerConnectionlessCommand::Print => Self::Print(PrintMessage::parse_packet(packet)?),
+            ServerConnectionlessCommand::KeyAuthorize => {
+                Self::KeyAuthorize(KeyAuthorizeMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::Motd => Self::Motd(MotdMessage::parse_packet(packet)?),
+            ServerConnectionlessCommand::GetServersResponse => {
+                Self::GetServersResponse(GetServersResponseMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::GetServersExtResponse => {
+                Self::GetServersExtResponse(GetServersExtResponseMessage::parse_packet(packet)?)
+            }
+            ServerConnectionlessCommand::Disconnect => {
+                Self::Disconnect(DisconnectMessage::parse_packet(packet)?)
+            }
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quake3::info::{InfoString, ParseErrorKind};
+
+    #[test]
+    fn sequencedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(SequencedPacket::new(
+            PacketSequenceNumber::new(42)?,
+            QPort::new(27960)?,
+            vec![0; FRAGMENT_SIZE]
+        )
+        .is_err());
+
+        assert!(SequencedPacket::new(
+            PacketSequenceNumber::new(42)?,
+            QPort::new(27960)?,
+            vec![0; FRAGMENT_SIZE - 1]
+        )
+        .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequencedpacket_transmit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+
+        assert!(SequencedPacket::transmit(&mut netchan, vec![0; FRAGMENT_SIZE]).is_err());
+
+        let packet = SequencedPacket::transmit(&mut netchan, vec![0; 42])?;
+        assert_eq!(packet.sequence(), PacketSequenceNumber::new(1)?);
+        assert_eq!(packet.qport(), QPort::new(27960)?);
+
+        let packet = SequencedPacket::transmit(&mut netchan, vec![0; 42])?;
+        assert_eq!(packet.sequence(), PacketSequenceNumber::new(2)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(FragmentedPacket::new(
+            PacketSequenceNumber::new(42)?,
+            QPort::new(27960)?,
+            FragmentStart::new(42)?,
+            vec![0; FRAGMENT_SIZE + 1]
+        )
+        .is_err());
+
+        assert!(FragmentedPacket::new(
+            PacketSequenceNumber::new(42)?,
+            QPort::new(27960)?,
+            FragmentStart::new(42)?,
+            vec![0; FRAGMENT_SIZE]
+        )
+        .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequencedpacket_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = SequencedPacket::new(
+            PacketSequenceNumber::new(0)?,
+            QPort::new(666)?,
+            &b"\xDE\xAD\xBE\xEF"[..],
+        )?;
+
+        let bytes = packet.to_bytes();
+        assert_eq!(bytes, &b"\x00\x00\x00\x00\x9A\x02\xDE\xAD\xBE\xEF"[..]);
+
+        let parsed = crate::server::parse_packet(bytes)?;
+        assert_eq!(parsed, crate::server::Packet::Sequenced(packet));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentedpacket_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = FragmentedPacket::new(
+            PacketSequenceNumber::new(0)?,
+            QPort::new(666)?,
+            FragmentStart::new(1)?,
+            &b"\xDE\xAD\xBE\xEF"[..],
+        )?;
+
+        let bytes = packet.to_bytes();
+        assert_eq!(
+            bytes,
+            &b"\x00\x00\x00\x80\x9A\x02\x01\x00\x04\x00\xDE\xAD\xBE\xEF"[..]
+        );
+
+        let parsed = crate::server::parse_packet(bytes)?;
+        assert_eq!(parsed, crate::server::Packet::Fragmented(packet));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequencedpacket_with_checksum_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let challenge = Challenge::new(0x12345678);
+        let packet = SequencedPacket::new(
+            PacketSequenceNumber::new(3)?,
+            QPort::new(666)?,
+            &b"\xDE\xAD\xBE\xEF"[..],
+        )?
+        .with_checksum(challenge);
+
+        let bytes = packet.to_bytes();
+        assert_eq!(
+            bytes,
+            &b"\x03\x00\x00\x00\x9A\x02\x10\x55\xA9\x24\xDE\xAD\xBE\xEF"[..]
+        );
+
+        let parsed =
+            crate::server::parse_packet_with(bytes.clone(), ProtocolVersion::IoQuake3, challenge)?;
+        assert_eq!(parsed, crate::server::Packet::Sequenced(packet));
+
+        assert!(matches!(
+            crate::server::parse_packet_with(
+                bytes,
+                ProtocolVersion::IoQuake3,
+                Challenge::new(0x0BADF00D)
+            ),
+            Err(crate::server::InvalidPacketError::InvalidChecksum)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequencedpacket_transmit_checksum() -> Result<(), Box<dyn std::error::Error>> {
+        let challenge = Challenge::new(0x12345678);
+        let mut netchan =
+            Netchan::with_protocol(QPort::new(27960)?, ProtocolVersion::IoQuake3, challenge);
+
+        let packet = SequencedPacket::transmit(&mut netchan, vec![0; 42])?;
+        assert_eq!(
+            packet.checksum(),
+            Some(NetchanChecksum::new(challenge, packet.sequence()))
+        );
+
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+        let packet = SequencedPacket::transmit(&mut netchan, vec![0; 42])?;
+        assert_eq!(packet.checksum(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn challengeresponsemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"challengeResponse -1234"[..])?;
+        let message = ChallengeResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Challenge::new(-1234));
+        assert_eq!(message.client_challenge(), None);
+        assert_eq!(message.protocol(), None);
+
+        let packet = ConnectionlessPacket::new(&b"challengeResponse 1234 5678 71"[..])?;
+        let message = ChallengeResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Challenge::new(1234));
+        assert_eq!(message.client_challenge(), Some(Challenge::new(5678)));
+        assert_eq!(message.protocol(), Some(ProtocolVersion::IoQuake3.number()));
+
+        for payload in [
+            &b"challengeResponse 123\n"[..],
+            b" challengeResponse 123",
+            b"challengeResponse \"123\"",
+        ] {
+            let packet = ConnectionlessPacket::new(payload)?;
+            let message = ChallengeResponseMessage::parse_packet(&packet)?;
+            assert_eq!(message.challenge(), Challenge::new(123));
+            assert_eq!(message.client_challenge(), None);
+        }
+
+        let packet = ConnectionlessPacket::new(&b"challengeResponse 1 2 71 extra"[..])?;
+        let message = ChallengeResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Challenge::new(1));
+        assert_eq!(message.client_challenge(), Some(Challenge::new(2)));
+        assert_eq!(message.protocol(), Some(71));
+
+        let packet = ConnectionlessPacket::new(&b"challengeResponse"[..])?;
+        assert!(ChallengeResponseMessage::parse_packet(&packet).is_err());
+
+        let packet = ConnectionlessPacket::new(&b"challengeResponse x"[..])?;
+        assert!(ChallengeResponseMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn challengeresponsemessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = ChallengeResponseMessage::new(Challenge::new(1234));
+        assert_eq!(
+            message.to_packet()?.payload(),
+            &b"challengeResponse 1234"[..]
+        );
+
+        let message = message
+            .with_client_challenge(Challenge::new(5678))
+            .with_protocol(71);
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"challengeResponse 1234 5678 71"[..]);
+        assert_eq!(ChallengeResponseMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn inforesponsemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"infoResponse\n\\challenge\\xxx\\gamename\\Quake3Arena\\protocol\\71\\hostname\\noname\\mapname\\q3dm17\\clients\\3\\g_humanplayers\\1\\sv_maxclients\\16\\gametype\\0\\pure\\1"[..],
+        )?;
+        let message = InfoResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Some(InfoStr::from_bytes(b"xxx")?));
+        assert_eq!(message.protocol()?, Some(71));
+        assert_eq!(message.hostname(), Some(InfoStr::from_bytes(b"noname")?));
+        assert_eq!(message.mapname(), Some(InfoStr::from_bytes(b"q3dm17")?));
+        assert_eq!(message.clients()?, Some(3));
+        assert_eq!(message.max_clients()?, Some(16));
+        assert_eq!(message.gametype()?, Some(0));
+
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(&b"infoResponse \\hostname\\noname"[..])?;
+        assert!(InfoResponseMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn inforesponsemessage_parse_lenient() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"infoResponse\n\\hostname\\noname\\\\x\\HOSTNAME\\other\\clients\\3\\"[..],
+        )?;
+        let message = InfoResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.hostname(), Some(InfoStr::from_bytes(b"noname")?));
+        assert_eq!(message.clients()?, Some(3));
+        assert_eq!(
+            message
+                .skipped()
+                .iter()
+                .map(|e| (e.offset(), e.kind()))
+                .collect::<Vec<_>>(),
+            [
+                (17, ParseErrorKind::EmptyKey),
+                (20, ParseErrorKind::DuplicateKey),
+                (44, ParseErrorKind::TrailingBackslash),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn inforesponsemessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let mut info = Info::new();
+        info.try_insert(
+            InfoString::from_bytes("hostname")?,
+            InfoString::from_bytes("noname")?,
+        )?;
+        info.try_insert(
+            InfoString::from_bytes("clients")?,
+            InfoString::from_bytes("many")?,
+        )?;
+
+        let packet = InfoResponseMessage::new(info).to_packet()?;
+        assert_eq!(
+            packet.payload(),
+            &b"infoResponse\n\\hostname\\noname\\clients\\many"[..]
+        );
+
+        let message = InfoResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.clients().map_err(|e| e.key()), Err("clients"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn statusresponsemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"statusResponse\n\\sv_hostname\\^1red ^7server\\mapname\\q3dm17\\challenge\\xxx\n12 50 \"^2Sarge\"\n-1 999 \"Unnamed Player\"\n"[..],
+        )?;
+        let message = StatusResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.info().len(), 3);
+        assert_eq!(message.challenge(), Some(InfoStr::from_bytes(b"xxx")?));
+        assert_eq!(
+            message.players(),
+            [
+                PlayerStatus::new(12, 50, QString::from_bytes(&b"^2Sarge"[..])?)?,
+                PlayerStatus::new(-1, 999, QString::from_bytes(&b"Unnamed Player"[..])?)?,
+            ]
+        );
+
+        assert_eq!(message.to_packet()?, packet);
+
+        Ok(())
+    }
+
+    #[test]
+    fn statusresponsemessage_parse_lenient() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"statusResponse\n\\sv_hostname\\noname\\mapname\n0 0 \"player\"\n"[..],
+        )?;
+        let message = StatusResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.info().len(), 1);
+        assert_eq!(message.players().len(), 1);
+        assert_eq!(
+            message
+                .skipped()
+                .iter()
+                .map(|e| (e.offset(), e.kind()))
+                .collect::<Vec<_>>(),
+            [(27, ParseErrorKind::MissingValue)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn statusresponsemessage_no_players() -> Result<(), Box<dyn std::error::Error>> {
+        let mut info = BigInfo::new();
+        info.try_insert(
+            InfoString::from_bytes("sv_hostname")?,
+            InfoString::from_bytes("noname")?,
+        )?;
+
+        let packet = StatusResponseMessage::new(info, vec![]).to_packet()?;
+        assert_eq!(
+            packet.payload(),
+            &b"statusResponse\n\\sv_hostname\\noname\n"[..]
+        );
+
+        let message = StatusResponseMessage::parse_packet(&packet)?;
+        assert!(message.players().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn playerstatus_new() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(PlayerStatus::new(0, 0, QString::from_bytes(&b"\"quoted\""[..])?).is_err());
+        assert!(PlayerStatus::new(0, 0, QString::from_bytes(&b"two\nlines"[..])?).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversresponsemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"getserversResponse\\\x7F\x00\x00\x01\x6D\x38\\\xC0\xA8\x00\x2A\x6D\x39"[..],
+        )?;
+        let message = GetServersResponseMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.servers(),
+            [
+                SocketAddrV4::new(Ipv4Addr::LOCALHOST, 27960),
+                SocketAddrV4::new(Ipv4Addr::new(192, 168, 0, 42), 27961),
+            ]
+        );
+        assert!(!message.is_last());
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(
+            &b"getserversResponse\\\x7F\x00\x00\x01\x6D\x38\\EOT\0\0\0"[..],
+        )?;
+        let message = GetServersResponseMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.servers(),
+            [SocketAddrV4::new(Ipv4Addr::LOCALHOST, 27960)]
+        );
+        assert!(message.is_last());
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet =
+            ConnectionlessPacket::new(&b"getserversResponse\\\x7F\x00\x00\x01\x6D\x38\\EOT"[..])?;
+        let message = GetServersResponseMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.servers(),
+            [SocketAddrV4::new(Ipv4Addr::LOCALHOST, 27960)]
+        );
+        assert!(message.is_last());
+
+        let packet = ConnectionlessPacket::new(&b"getserversResponse\\\x7F\x00"[..])?;
+        assert!(GetServersResponseMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversresponsemessage_split() -> Result<(), Box<dyn std::error::Error>> {
+        let servers: Vec<_> = (0..MAX_GETSERVERSRESPONSE_SERVERS as u16 + 1)
+            .map(|port| SocketAddrV4::new(Ipv4Addr::LOCALHOST, port))
+            .collect();
+
+        let messages = GetServersResponseMessage::split(&servers);
+        assert_eq!(messages.len(), 2);
+
+        let mut list = ServerList::new();
+        for message in messages {
+            let packet = message.to_packet()?;
+            assert!(packet.to_bytes().len() <= MAX_PACKETLEN);
+            list.push(GetServersResponseMessage::parse_packet(&packet)?);
+        }
+        assert!(list.is_complete());
+        assert!(list
+            .into_servers()
+            .into_iter()
+            .eq(servers.into_iter().map(SocketAddr::V4)));
+
+        let messages = GetServersResponseMessage::split(&[]);
+        assert_eq!(messages, [GetServersResponseMessage::new(vec![], true)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn printmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"print\nServer is full.\n"[..])?;
+        let message = PrintMessage::parse_packet(&packet)?;
+        assert_eq!(message.text(), QStr::from_bytes(b"Server is full.\n")?);
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(&b" PRINT ignored\nhello"[..])?;
+        let message = PrintMessage::parse_packet(&packet)?;
+        assert_eq!(message.text(), QStr::from_bytes(b"hello")?);
+
+        let packet = ConnectionlessPacket::new(&b"print"[..])?;
+        assert!(PrintMessage::parse_packet(&packet).is_err());
+
+        let packet = ConnectionlessPacket::new(&b"printer\nhello"[..])?;
+        assert!(PrintMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn echomessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"echo \"hello world\" ignored"[..])?;
+        let message = EchoMessage::parse_packet(&packet)?;
+        assert_eq!(message.text(), QStr::from_bytes(b"hello world")?);
+        assert_eq!(message.reply_packet()?.payload(), &b"hello world"[..]);
+
+        let packet = ConnectionlessPacket::new(&b"echo"[..])?;
+        let message = EchoMessage::parse_packet(&packet)?;
+        assert_eq!(message.text(), QStr::from_bytes(b"")?);
+
+        let packet = ConnectionlessPacket::new(&b"echoes"[..])?;
+        assert!(EchoMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn echomessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(EchoMessage::new(QString::from_bytes(&b"say \"hi\""[..])?).is_err());
+
+        let message = EchoMessage::new(QString::from_bytes(&b"hello world"[..])?)?;
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"echo \"hello world\""[..]);
+        assert_eq!(EchoMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn disconnectmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = DisconnectMessage::new().to_packet()?;
+        assert_eq!(packet.payload(), &b"disconnect"[..]);
+        assert_eq!(
+            DisconnectMessage::parse_packet(&packet)?,
+            DisconnectMessage::new()
+        );
+
+        let packet = ConnectionlessPacket::new(&b"disconnected"[..])?;
+        assert!(DisconnectMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectresponsemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"connectResponse"[..])?;
+        let message = ConnectResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), None);
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(&b"connectResponse -1234"[..])?;
+        let message = ConnectResponseMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Some(Challenge::new(-1234)));
+        assert_eq!(message.to_packet()?, packet);
+
+        for (payload, challenge) in [
+            (&b"connectResponse 5\n"[..], 5),
+            (b"connectResponse \"5\"", 5),
+            (b"connectResponse 1 x", 1),
+            (b" connectresponse 7", 7),
+        ] {
+            let packet = ConnectionlessPacket::new(payload)?;
+            let message = ConnectResponseMessage::parse_packet(&packet)?;
+            assert_eq!(message.challenge(), Some(Challenge::new(challenge)));
+        }
+
+        let packet = ConnectionlessPacket::new(&b"connectResponse abc"[..])?;
+        assert!(ConnectResponseMessage::parse_packet(&packet).is_err());
+
+        let packet = ConnectionlessPacket::new(&b"connectResponses"[..])?;
+        assert!(ConnectResponseMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn keyauthorizemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"keyAuthorize 1 accept"[..])?;
+        let message = KeyAuthorizeMessage::parse_packet(&packet)?;
+        assert_eq!(message.arguments(), QStr::from_bytes(b"1 accept")?);
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(&b"keyAuthorize"[..])?;
+        let message = KeyAuthorizeMessage::parse_packet(&packet)?;
+        assert_eq!(message.arguments(), QStr::from_bytes(b"")?);
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(&b"  KEYAUTHORIZE  1 \"x y\"\nignored"[..])?;
+        let message = KeyAuthorizeMessage::parse_packet(&packet)?;
+        assert_eq!(message.arguments(), QStr::from_bytes(b"1 \"x y\"")?);
+
+        let packet = ConnectionlessPacket::new(&b"keyAuthorized 1"[..])?;
+        assert!(KeyAuthorizeMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn motdmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"motd \"\\challenge\\1234\\motd\\Welcome to Quake 3\""[..],
+        )?;
+        let message = MotdMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Some(InfoStr::from_bytes(b"1234")?));
+        assert_eq!(
+            message.motd(),
+            Some(InfoStr::from_bytes(b"Welcome to Quake 3")?)
+        );
+        assert_eq!(message.to_packet()?, packet);
+        assert!(message.skipped().is_empty());
+
+        let packet = ConnectionlessPacket::new(&b"motd \"\\challenge\\1234\\motd\""[..])?;
+        let message = MotdMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Some(InfoStr::from_bytes(b"1234")?));
+        assert_eq!(message.motd(), None);
+        assert_eq!(message.skipped().len(), 1);
+
+        let info = Info::parse(b"\\motd\\say \"hi\"")?;
+        assert!(MotdMessage::new(info).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn serverconnectionlessmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"CHALLENGERESPONSE 42"[..])?;
+        let message = ServerConnectionlessMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.command(),
+            ServerConnectionlessCommand::ChallengeResponse
+        );
+
+        let packet = ConnectionlessPacket::new(&b"print\nbad rcon password\n"[..])?;
+        let ServerConnectionlessMessage::Print(message) =
+            ServerConnectionlessMessage::parse_packet(&packet)?
+        else {
+            panic!("should be print");
+        };
+        assert_eq!(message.text(), QStr::from_bytes(b"bad rcon password\n")?);
+
+        let packet = ConnectionlessPacket::new(
+            &b"getserversResponse\\\x7F\x00\x00\x01\x6D\x38\\EOT\0\0\0"[..],
+        )?;
+        let message = ServerConnectionlessMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.command(),
+            ServerConnectionlessCommand::GetServersResponse
+        );
+
+        let packet = GetServersExtResponseMessage::new(
+            vec![SocketAddr::from((Ipv6Addr::LOCALHOST, 27960))],
+            true,
+        )
+        .to_packet()?;
+        let message = ServerConnectionlessMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.command(),
+            ServerConnectionlessCommand::GetServersExtResponse
+        );
+
+        let packet = ConnectionlessPacket::new(&b"statusResponse"[..])?;
+        assert_eq!(
+            ServerConnectionlessMessage::parse_packet(&packet).err(),
+            Some(ParseServerConnectionlessMessageError::StatusResponse(
+                ParseStatusResponseMessageError(())
+            ))
+        );
+
+        let packet = ConnectionlessPacket::new(&b"getchallenge"[..])?;
+        match ServerConnectionlessMessage::parse_packet(&packet) {
+            Err(ParseServerConnectionlessMessageError::UnknownCommand(e)) => {
+                assert_eq!(e.command(), b"getchallenge")
+            }
+            _ => panic!("should be unknown"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rconoutput_push() -> Result<(), Box<dyn std::error::Error>> {
+        let status: Vec<u8> = (0..100)
+            .flat_map(|i| format!("{i:3}     0   50 UnnamedPlayer\n").into_bytes())
+            .collect();
+        let status = QString::from_bytes(status)?;
+
+        let messages = PrintMessage::split(&status);
+        assert!(messages.len() > 1);
+
+        let mut output = RconOutput::new();
+        for message in messages {
+            let packet = message.to_packet()?;
+            assert!(packet.payload().len() < 1024);
+            output.push(PrintMessage::parse_packet(&packet)?);
+        }
+        assert_eq!(output.text(), &*status);
+
+        let messages = PrintMessage::split(QStr::from_bytes(b"")?);
+        assert_eq!(
+            messages,
+            [PrintMessage::new(QString::from_bytes(&b""[..])?)]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversextresponsemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(
+            &b"getserversExtResponse\\\x7F\x00\x00\x01\x6D\x38/\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x6D\x39\\EOT\0\0\0"[..],
+        )?;
+        let message = GetServersExtResponseMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.servers(),
+            [
+                SocketAddr::from((Ipv4Addr::LOCALHOST, 27960)),
+                SocketAddr::from((Ipv6Addr::LOCALHOST, 27961)),
+            ]
+        );
+        assert!(message.is_last());
+        assert_eq!(message.to_packet()?, packet);
+
+        let packet = ConnectionlessPacket::new(
+            &b"getserversExtResponse/\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x00\x01\x6D\x39\\EOT"[..],
+        )?;
+        let message = GetServersExtResponseMessage::parse_packet(&packet)?;
+        assert_eq!(
+            message.servers(),
+            [SocketAddr::from((Ipv6Addr::LOCALHOST, 27961))]
+        );
+        assert!(message.is_last());
+
+        let packet = ConnectionlessPacket::new(&b"getserversExtResponse/\x00\x00"[..])?;
+        assert!(GetServersExtResponseMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversextresponsemessage_split() -> Result<(), Box<dyn std::error::Error>> {
+        let servers: Vec<_> = (0..200)
+            .flat_map(|port| {
+                [
+                    SocketAddr::from((Ipv4Addr::LOCALHOST, port)),
+                    SocketAddr::from((Ipv6Addr::LOCALHOST, port)),
+                ]
+            })
+            .collect();
+
+        let messages = GetServersExtResponseMessage::split(&servers);
+        assert!(messages.len() > 1);
+
+        let mut list = ServerList::new();
+        for message in messages {
+            let packet = message.to_packet()?;
+            assert!(packet.to_bytes().len() <= MAX_PACKETLEN);
+            let last = message.is_last();
+            assert_eq!(
+                list.push_ext(GetServersExtResponseMessage::parse_packet(&packet)?),
+                last
+            );
+        }
+        assert!(list.is_complete());
+        assert_eq!(list.into_servers(), servers);
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_server_packet_invalidsize() {
+        for mut payload in [
+            &b"\xFF"[..],
+            &b"\x00\x00\x00\x80\x01"[..],
+            &b"\x00\x00\x00\x80\x01\x00\x04"[..],
+            // the fragment length does not match the payload
+            &b"\x00\x00\x00\x80\x01\x00\x05\x00\xDE\xAD\xBE\xEF"[..],
+            &b"\x00\x00\x00\x80\x01\x00\x03\x00\xDE\xAD\xBE\xEF"[..],
+        ] {
+            let packet = parse_server_packet(&mut payload);
+            assert!(matches!(packet, Err(InvalidServerPacketError::InvalidSize)));
+        }
+
+        let mut payload = &b"\x00\x00\x00\x00\x01\x00"[..];
+        let packet = parse_server_packet_with(
+            &mut payload,
+            ProtocolVersion::IoQuake3,
+            Challenge::default(),
+        );
+        assert!(matches!(packet, Err(InvalidServerPacketError::InvalidSize)));
     }
 
     #[test]
@@ -0,0 +1,218 @@
+//! Command lines of connectionless messages
+//!
+//! This is the equivalent of the parts of `cmd.c` that split connectionless payloads into arguments.
+
+use bytes::BufMut;
+use std::ffi::c_int;
+use winnow::ascii::dec_int;
+use winnow::token::take_while;
+use winnow::ModalResult;
+use winnow::Parser;
+
+/// Whitespace between arguments, which is any control character or space in `Cmd_TokenizeString`
+pub(crate) fn separator(input: &mut &[u8]) -> ModalResult<()> {
+    take_while(1.., |c: u8| c <= b' ').void().parse_next(input)
+}
+
+/// Integer argument, like `atoi` but without trailing garbage
+pub(crate) fn int_argument(input: &mut &[u8]) -> ModalResult<c_int> {
+    dec_int.parse_next(input)
+}
+
+/// Maximum number of arguments, like `MAX_STRING_TOKENS`
+pub(crate) const MAX_STRING_TOKENS: usize = 1024;
+
+fn is_line_comment(line: &[u8], i: usize) -> bool {
+    // accept protocol headers like http:// as in Cmd_TokenizeString2
+    line[i..].starts_with(b"//") && !(i > 0 && line[i - 1] == b':')
+}
+
+/// Arguments of a command line like `Cmd_TokenizeString`
+///
+/// Arguments are separated by whitespace and may be quoted, `//` and `/* */` comments are skipped.
+/// The line ends at the first `\0` like a C string.
+/// Unlike the engine this does not truncate the line to `MAX_STRING_CHARS`.
+pub(crate) struct Tokens<'s> {
+    line: &'s [u8],
+    i: usize,
+    count: usize,
+}
+
+impl<'s> Tokens<'s> {
+    pub(crate) fn new(line: &'s [u8]) -> Self {
+        let line = match line.iter().position(|&c| c == b'\0') {
+            Some(len) => &line[..len],
+            None => line,
+        };
+        Self {
+            line,
+            i: 0,
+            count: 0,
+        }
+    }
+
+    /// Rest of the line after the last argument, like the raw `Cmd_Cmd` that `SVC_RemoteCommand` skips through
+    pub(crate) fn rest(&self) -> &'s [u8] {
+        &self.line[self.i..]
+    }
+
+    fn skip_whitespace_and_comments(&mut self) -> bool {
+        let line = self.line;
+        loop {
+            while self.i < line.len() && line[self.i] <= b' ' {
+                self.i += 1;
+            }
+            if self.i == line.len() || is_line_comment(line, self.i) {
+                return false;
+            }
+            if !line[self.i..].starts_with(b"/*") {
+                return true;
+            }
+            match line[self.i + 2..].windows(2).position(|w| w == b"*/") {
+                Some(end) => self.i += 2 + end + 2,
+                None => return false,
+            }
+        }
+    }
+}
+
+impl<'s> Iterator for Tokens<'s> {
+    type Item = &'s [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.count == MAX_STRING_TOKENS || !self.skip_whitespace_and_comments() {
+            self.i = self.line.len();
+            return None;
+        }
+        self.count += 1;
+
+        let line = self.line;
+        if line[self.i] == b'"' {
+            let start = self.i + 1;
+            return match line[start..].iter().position(|&c| c == b'"') {
+                Some(len) => {
+                    self.i = start + len + 1;
+                    Some(&line[start..start + len])
+                }
+                None => {
+                    self.i = line.len();
+                    Some(&line[start..])
+                }
+            };
+        }
+
+        let start = self.i;
+        while self.i < line.len()
+            && line[self.i] > b' '
+            && line[self.i] != b'"'
+            && !is_line_comment(line, self.i)
+            && !line[self.i..].starts_with(b"/*")
+        {
+            self.i += 1;
+        }
+        Some(&line[start..self.i])
+    }
+}
+
+/// Arguments of a connectionless payload after the first one, if that is `command`
+///
+/// The command line ends at the first newline like `MSG_ReadStringLine`, and `command` is case insensitive like `Q_stricmp`.
+pub(crate) fn command_arguments<'s>(payload: &'s [u8], command: &[u8]) -> Option<Tokens<'s>> {
+    let line = payload.split(|&c| c == b'\n').next().unwrap_or_default();
+    let mut arguments = Tokens::new(line);
+    arguments
+        .next()
+        .is_some_and(|first| first.eq_ignore_ascii_case(command))
+        .then_some(arguments)
+}
+
+/// Split a command line into all its arguments, see [`Tokens`]
+pub(crate) fn tokenize(line: &[u8]) -> Vec<&[u8]> {
+    Tokens::new(line).collect()
+}
+
+/// Whether `bytes` can be written unquoted and is read back as exactly one argument
+pub(crate) fn is_argument(bytes: &[u8]) -> bool {
+    tokenize(bytes) == [bytes]
+}
+
+/// Write a space and an integer argument, like `%i`
+pub(crate) fn write_int_argument(buf: &mut impl BufMut, value: c_int) {
+    buf.put_u8(b' ');
+    buf.put_slice(value.to_string().as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn int_argument_sign() {
+        assert_eq!(int_argument.parse(&b"-42"[..]), Ok(-42));
+        assert!(int_argument.parse(&b"42abc"[..]).is_err());
+    }
+
+    #[test]
+    fn tokenize_quotes() {
+        assert_eq!(
+            tokenize(b"say \"hello world\"  \t 42"),
+            [&b"say"[..], b"hello world", b"42"]
+        );
+        assert_eq!(tokenize(b"a\"b\"c"), [&b"a"[..], b"b", b"c"]);
+        assert_eq!(
+            tokenize(b"say \"unterminated"),
+            [&b"say"[..], b"unterminated"]
+        );
+        assert!(tokenize(b"  \n").is_empty());
+    }
+
+    #[test]
+    fn tokenize_comments() {
+        assert_eq!(tokenize(b"map q3dm17 // comment"), [&b"map"[..], b"q3dm17"]);
+        assert_eq!(tokenize(b"map/* c */q3dm17"), [&b"map"[..], b"q3dm17"]);
+        assert_eq!(tokenize(b"map /* unterminated"), [&b"map"[..]]);
+        assert_eq!(
+            tokenize(b"sv_dlURL http://example.com"),
+            [&b"sv_dlURL"[..], b"http://example.com"]
+        );
+        assert_eq!(tokenize(b"A://x"), [&b"A://x"[..]]);
+        assert_eq!(tokenize(b"1:// 2"), [&b"1://"[..], b"2"]);
+        assert_eq!(tokenize(b"a ://x"), [&b"a"[..], b"://x"]);
+    }
+
+    #[test]
+    fn tokenize_nul() {
+        assert_eq!(tokenize(b"say a\0b"), [&b"say"[..], b"a"]);
+        assert_eq!(tokenize(b"say \"a\0b\" c"), [&b"say"[..], b"a"]);
+        assert!(tokenize(b"\0say").is_empty());
+    }
+
+    #[test]
+    fn command_arguments_line() {
+        let mut arguments =
+            command_arguments(b"  RCON secret status\nquit", b"rcon").expect("should be rcon");
+        assert_eq!(arguments.next(), Some(&b"secret"[..]));
+        assert_eq!(arguments.rest(), b" status");
+        assert_eq!(arguments.next(), Some(&b"status"[..]));
+        assert_eq!(arguments.next(), None);
+
+        assert!(command_arguments(b"rcon2 secret", b"rcon").is_none());
+        assert!(command_arguments(b"\nrcon secret", b"rcon").is_none());
+    }
+
+    #[test]
+    fn is_argument_single() {
+        assert!(is_argument(b"Quake3Arena"));
+        assert!(!is_argument(b""));
+        assert!(!is_argument(b"foo bar"));
+        assert!(!is_argument(b"a\"b"));
+        assert!(!is_argument(b"a//b"));
+        assert!(!is_argument(b"a\nb"));
+    }
+
+    #[test]
+    fn separator_control() {
+        assert!(separator.parse(&b" \t\r\n"[..]).is_ok());
+        assert!(separator.parse(&b""[..]).is_err());
+    }
+}
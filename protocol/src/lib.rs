@@ -1,5 +1,7 @@
+use crate::msg::MAX_MSGLEN;
 use crate::net::chan::{FRAGMENT_BIT, FRAGMENT_SIZE, MAX_PACKETLEN};
-use bytes::Bytes;
+use bytes::{BufMut, Bytes, BytesMut};
+use quake3::info::{InfoMap, InfoStr, InfoString};
 use std::ffi::{c_int, c_ushort};
 
 use winnow::binary::le_i32;
@@ -7,8 +9,11 @@ use winnow::error::{AddContext, ParserError, StrContext, StrContextValue};
 use winnow::Parser;
 
 pub mod client;
+mod cmd;
+pub mod msg;
 pub mod net;
 pub mod server;
+pub mod userinfo;
 
 const CONNECTIONLESS_SEQUENCE: c_int = 0xFF_FF_FF_FFu32 as i32;
 
@@ -22,14 +27,41 @@ pub struct PacketSequenceNumber(c_int);
 
 impl PacketSequenceNumber {
     pub fn new(bits: c_int) -> Result<Self, InvalidPacketSequenceNumberError> {
-        if CONNECTIONLESS_SEQUENCE == bits {
-            Err(InvalidPacketSequenceNumberError(()))
-        } else if bits & FRAGMENT_BIT != 0 {
+        if CONNECTIONLESS_SEQUENCE == bits || bits & FRAGMENT_BIT != 0 {
             Err(InvalidPacketSequenceNumberError(()))
         } else {
             Ok(Self(bits))
         }
     }
+
+    /// Sequence number after this one, wrapping around before [`FRAGMENT_BIT`]
+    pub fn next(self) -> Self {
+        self.wrapping_add(1)
+    }
+
+    pub fn wrapping_add(self, n: u32) -> Self {
+        Self((self.0 as u32).wrapping_add(n) as c_int & !FRAGMENT_BIT)
+    }
+
+    /// Number of sequence numbers from `earlier` to this one, with wrapping
+    pub fn wrapping_distance(self, earlier: Self) -> u32 {
+        (self.0 as u32).wrapping_sub(earlier.0 as u32) & !FRAGMENT_BIT as u32
+    }
+
+    /// Whether this is after `other` in serial number arithmetic
+    ///
+    /// Unlike [`Ord`] this keeps working after the sequence wrapped around,
+    /// as long as both are less than half of the sequence space apart.
+    pub fn is_after(self, other: Self) -> bool {
+        let distance = self.wrapping_distance(other);
+        distance != 0 && distance < (FRAGMENT_BIT as u32) >> 1
+    }
+}
+
+impl std::convert::From<PacketSequenceNumber> for c_int {
+    fn from(item: PacketSequenceNumber) -> Self {
+        item.0
+    }
 }
 
 #[repr(transparent)]
@@ -56,6 +88,10 @@ impl PacketSequence {
     pub fn number(&self) -> PacketSequenceNumber {
         PacketSequenceNumber(self.0 & !FRAGMENT_BIT)
     }
+
+    pub(crate) fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_i32_le(self.0);
+    }
 }
 
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -74,6 +110,8 @@ impl PacketKind {
     }
 }
 
+// not called yet, this is the first step of the winnow based parse_packet() sketched in server.rs
+#[allow(dead_code)]
 pub(crate) fn parse_packetkind<'s, E>() -> impl Parser<&'s [u8], PacketKind, E>
 where
     E: AddContext<&'s [u8], StrContext> + ParserError<&'s [u8]>,
@@ -118,6 +156,17 @@ impl ConnectionlessPacket {
     pub fn payload(&self) -> &Bytes {
         &self.payload
     }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_i32_le(CONNECTIONLESS_SEQUENCE);
+        buf.put_slice(&self.payload);
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(core::mem::size_of::<c_int>() + self.payload.len());
+        self.write_to(&mut buf);
+        buf.freeze()
+    }
 }
 
 #[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -131,7 +180,8 @@ pub struct FragmentStart(c_ushort);
 impl FragmentStart {
     // TODO: new_unchecked to create oversize fragment start?
     pub fn new(start: c_ushort) -> Result<Self, InvalidFragmentStartError> {
-        if usize::from(start) >= MAX_PACKETLEN {
+        // this is an offset into the whole message, not into a single packet
+        if usize::from(start) >= MAX_MSGLEN {
             Err(InvalidFragmentStartError(()))
         } else {
             Ok(Self(start))
@@ -139,6 +189,23 @@ impl FragmentStart {
     }
 }
 
+impl std::convert::From<FragmentStart> for usize {
+    fn from(item: FragmentStart) -> Self {
+        item.0 as Self
+    }
+}
+
+impl std::convert::TryFrom<usize> for FragmentStart {
+    type Error = InvalidFragmentStartError;
+
+    fn try_from(value: usize) -> Result<Self, Self::Error> {
+        let start: c_ushort = value
+            .try_into()
+            .map_err(|_| InvalidFragmentStartError(()))?;
+        FragmentStart::new(start)
+    }
+}
+
 #[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[error("is invalid")]
 pub struct InvalidFragmentLengthError(());
@@ -201,6 +268,82 @@ impl FragmentInfo {
     pub fn is_last(&self) -> bool {
         self.length.is_last_fragment()
     }
+
+    pub(crate) fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_u16_le(self.start.0);
+        buf.put_u16_le(self.length.0);
+    }
+}
+
+#[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidProtocolVersionError(());
+
+/// Version of the network protocol
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ProtocolVersion {
+    /// id Quake 3 v1.32c
+    Quake3,
+    /// ioQuake3 v1.36 with a checksum in sequenced packets
+    IoQuake3,
+}
+
+impl ProtocolVersion {
+    pub const fn number(&self) -> c_int {
+        match self {
+            Self::Quake3 => 68,
+            Self::IoQuake3 => 71,
+        }
+    }
+
+    /// Whether sequenced packets have a [`NetchanChecksum`] after the qport
+    pub const fn has_checksum(&self) -> bool {
+        matches!(self, Self::IoQuake3)
+    }
+}
+
+impl std::convert::TryFrom<c_int> for ProtocolVersion {
+    type Error = InvalidProtocolVersionError;
+
+    fn try_from(value: c_int) -> Result<Self, Self::Error> {
+        match value {
+            68 => Ok(Self::Quake3),
+            71 => Ok(Self::IoQuake3),
+            _ => Err(InvalidProtocolVersionError(())),
+        }
+    }
+}
+
+/// Challenge of a connection, as handed out by the server in `challengeResponse`
+#[repr(transparent)]
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Challenge(c_int);
+
+impl Challenge {
+    pub fn new(bits: c_int) -> Self {
+        Self(bits)
+    }
+}
+
+impl std::convert::From<Challenge> for c_int {
+    fn from(item: Challenge) -> Self {
+        item.0
+    }
+}
+
+/// Checksum of ioq3 sequenced packets against UDP spoofing, see `NETCHAN_GENCHECKSUM`
+#[repr(transparent)]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct NetchanChecksum(c_int);
+
+impl NetchanChecksum {
+    pub fn new(challenge: Challenge, sequence: PacketSequenceNumber) -> Self {
+        Self(challenge.0 ^ sequence.0.wrapping_mul(challenge.0))
+    }
+
+    pub(crate) fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_i32_le(self.0);
+    }
 }
 
 #[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -223,12 +366,92 @@ impl QPort {
     }
 }
 
+impl std::convert::From<QPort> for c_ushort {
+    fn from(item: QPort) -> Self {
+        item.0
+    }
+}
+
+/// Error for a value of an info string that does not have the expected type
+#[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("invalid value for {key}")]
+pub struct InvalidInfoValueError {
+    key: &'static str,
+}
+
+impl InvalidInfoValueError {
+    pub fn key(&self) -> &'static str {
+        self.key
+    }
+}
+
+/// Error for a connectionless message with an unknown command
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is unknown")]
+pub struct UnknownCommandError {
+    command: Bytes,
+}
+
+impl UnknownCommandError {
+    pub(crate) fn new(command: &[u8]) -> Self {
+        Self {
+            command: Bytes::copy_from_slice(command),
+        }
+    }
+
+    /// First argument of the payload, which is empty if there is none
+    pub fn command(&self) -> &[u8] {
+        &self.command
+    }
+}
+
+/// Value of the first key that matches case insensitively, like `Q_stricmp` in `Info_ValueForKey`
+pub(crate) fn info_value<'i, const L: usize>(
+    info: &'i InfoMap<InfoString, InfoString, L>,
+    key: &str,
+) -> Option<&'i InfoStr> {
+    info.iter()
+        .find(|(k, _v)| k.as_bytes().eq_ignore_ascii_case(key.as_bytes()))
+        .map(|(_k, value)| &**value)
+}
+
+/// Integer value, like `atoi` but without trailing garbage
+pub(crate) fn info_int<const L: usize>(
+    info: &InfoMap<InfoString, InfoString, L>,
+    key: &'static str,
+) -> Result<Option<c_int>, InvalidInfoValueError> {
+    info_value(info, key)
+        .map(|value| {
+            core::str::from_utf8(value.as_bytes())
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .ok_or(InvalidInfoValueError { key })
+        })
+        .transpose()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    use quake3::info::INFO_LIMIT;
     use winnow::error::ContextError;
 
+    #[test]
+    fn info_value_caseless() -> Result<(), Box<dyn std::error::Error>> {
+        let info = InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(
+            b"\\HOSTNAME\\x\\hostname\\y\\Sv_MaxClients\\16",
+        )?;
+        assert_eq!(
+            info_value(&info, "hostname"),
+            Some(InfoStr::from_bytes(b"x")?)
+        );
+        assert_eq!(info_int(&info, "sv_maxclients")?, Some(16));
+        assert_eq!(info_value(&info, "mapname"), None);
+
+        Ok(())
+    }
+
     #[test]
     fn packetsequencenumber_new() {
         assert!(PacketSequenceNumber::new(CONNECTIONLESS_SEQUENCE).is_err());
@@ -238,6 +461,26 @@ mod tests {
         assert!(PacketSequenceNumber::new(42).is_ok());
     }
 
+    #[test]
+    fn packetsequencenumber_wrapping() -> Result<(), Box<dyn std::error::Error>> {
+        let first = PacketSequenceNumber::new(0)?;
+        let last = PacketSequenceNumber::new(!FRAGMENT_BIT)?;
+
+        assert_eq!(first.next(), PacketSequenceNumber::new(1)?);
+        assert_eq!(last.next(), first);
+        assert_eq!(last.wrapping_add(3), PacketSequenceNumber::new(2)?);
+
+        assert_eq!(first.wrapping_distance(last), 1);
+        assert_eq!(last.wrapping_distance(first), !FRAGMENT_BIT as u32);
+
+        assert!(first.is_after(last));
+        assert!(!last.is_after(first));
+        assert!(!first.is_after(first));
+        assert!(PacketSequenceNumber::new(42)?.is_after(first));
+
+        Ok(())
+    }
+
     #[test]
     fn packetsequence_new_with_number_and_fragment() -> Result<(), Box<dyn std::error::Error>> {
         let sequence =
@@ -303,11 +546,25 @@ mod tests {
         assert!(ConnectionlessPacket::new(&[0xDE, 0xAD, 0xBE, 0xEF][..]).is_ok());
     }
 
+    #[test]
+    fn connectionlesspacket_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"getinfo xxx"[..])?;
+
+        assert_eq!(packet.to_bytes(), &b"\xFF\xFF\xFF\xFFgetinfo xxx"[..]);
+
+        let parsed = crate::server::parse_packet(packet.to_bytes())?;
+        assert_eq!(parsed, crate::server::Packet::Connectionless(packet));
+
+        Ok(())
+    }
+
     #[test]
     fn fragmentstart_new() {
-        assert!(FragmentStart::new(MAX_PACKETLEN as c_ushort).is_err());
+        assert!(FragmentStart::new(MAX_MSGLEN as c_ushort).is_err());
 
         assert!(FragmentStart::new(42).is_ok());
+
+        assert!(FragmentStart::new(FRAGMENT_SIZE as c_ushort * 2).is_ok());
     }
 
     #[test]
@@ -328,6 +585,31 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn protocolversion_try_from() {
+        assert_eq!(ProtocolVersion::try_from(68), Ok(ProtocolVersion::Quake3));
+        assert_eq!(ProtocolVersion::try_from(71), Ok(ProtocolVersion::IoQuake3));
+        assert!(ProtocolVersion::try_from(43).is_err());
+
+        assert_eq!(ProtocolVersion::IoQuake3.number(), 71);
+    }
+
+    #[test]
+    fn netchanchecksum_new() -> Result<(), Box<dyn std::error::Error>> {
+        let challenge = Challenge::new(0x1234_5678);
+
+        assert_eq!(
+            NetchanChecksum::new(challenge, PacketSequenceNumber::new(0)?),
+            NetchanChecksum(0x1234_5678)
+        );
+        assert_eq!(
+            NetchanChecksum::new(challenge, PacketSequenceNumber::new(3)?),
+            NetchanChecksum(0x1234_5678 ^ 0x369D_0368)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn qport_new() {
         assert!(QPort::new(0).is_err());
@@ -0,0 +1,483 @@
+//! Bit-level message buffers
+//!
+//! This is the equivalent of `msg_t` and the `MSG_Read*` / `MSG_Write*` functions.
+//! The payload of a sequenced packet is such a message, which contains the `svc_*` and `clc_*` commands.
+//!
+//! A message is either [`Encoding::Raw`] like after `MSG_InitOOB` or `MSG_BeginReadingOOB`,
+//! or [`Encoding::Huffman`] like after `MSG_Bitstream`.
+
+use bitvec::field::BitField;
+use bitvec::order::Lsb0;
+use bitvec::slice::BitSlice;
+use bitvec::vec::BitVec;
+use bitvec::view::BitView;
+use bytes::Bytes;
+use quake3::qstr::{QStr, QString};
+use quake3_huffman::Huffman;
+use std::sync::OnceLock;
+
+/// Maximum size of a whole message, i.e. the reassembled payload of sequenced packets
+pub const MAX_MSGLEN: usize = 16384;
+
+/// Maximum length of a string including the terminating `\0`
+pub const MAX_STRING_CHARS: usize = 1024;
+
+/// Maximum length of a big string including the terminating `\0`
+pub const BIG_INFO_STRING: usize = 8192;
+
+/// How values are laid out in a [`MessageWriter`] or [`MessageReader`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Encoding {
+    /// Byte-aligned values which are either 8, 16 or 32 bits wide
+    Raw,
+    /// Bitstream with every whole byte of a value coded by the fixed [`Huffman`] tree
+    Huffman,
+}
+
+/// Error for [`MessageWriter`] and [`MessageReader`]
+#[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum MessageError {
+    #[error("invalid number of bits {0}")]
+    InvalidBits(u32),
+    #[error("overflowed")]
+    Overflow,
+    #[error("string too long")]
+    StringTooLong,
+}
+
+// MSG_initHuffman() only runs once per process, and building the tree is costly
+fn huffman() -> Box<Huffman> {
+    static HUFFMAN: OnceLock<Huffman> = OnceLock::new();
+
+    Box::new(HUFFMAN.get_or_init(Huffman::fixed).clone())
+}
+
+fn check_bits(encoding: Encoding, bits: u32) -> Result<(), MessageError> {
+    let valid = match encoding {
+        Encoding::Raw => matches!(bits, 8 | 16 | 32),
+        Encoding::Huffman => (1..=32).contains(&bits),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(MessageError::InvalidBits(bits))
+    }
+}
+
+/// Outgoing message, like `MSG_Init`
+#[derive(Clone, Debug)]
+pub struct MessageWriter {
+    bits: BitVec<u8, Lsb0>,
+    encoding: Encoding,
+    huffman: Option<Box<Huffman>>,
+}
+
+impl MessageWriter {
+    pub fn new(encoding: Encoding) -> Self {
+        let mut writer = Self {
+            bits: BitVec::new(),
+            encoding: Encoding::Raw,
+            huffman: None,
+        };
+        writer.set_encoding(encoding);
+        writer
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Switch encoding for all following values, e.g. to [`Encoding::Huffman`] like `MSG_Bitstream`
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        if Encoding::Huffman == encoding && self.huffman.is_none() {
+            self.huffman = Some(huffman());
+        }
+        self.encoding = encoding;
+    }
+
+    /// Number of bits written so far
+    pub fn bit_len(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Number of bytes written so far, including a partially written last byte
+    pub fn len(&self) -> usize {
+        self.bits.as_raw_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bits.is_empty()
+    }
+
+    /// Write the lower `bits` of `value`, like `MSG_WriteBits`
+    pub fn write_bits(&mut self, value: u32, bits: u32) -> Result<(), MessageError> {
+        check_bits(self.encoding, bits)?;
+        // the Huffman code of a byte can be longer than 8 bits, so this is not exact for Encoding::Huffman
+        if self.bit_len() + bits as usize > MAX_MSGLEN * 8 {
+            return Err(MessageError::Overflow);
+        }
+
+        match self.encoding {
+            Encoding::Raw => {
+                let bytes = value.to_le_bytes();
+                let len = bits as usize / 8;
+                self.bits
+                    .extend_from_bitslice(bytes[..len].view_bits::<Lsb0>());
+            }
+            Encoding::Huffman => {
+                let mut value = value;
+                let mut bits = bits;
+
+                // leftover bits are written as-is first, only whole bytes are coded
+                let nbits = bits & 7;
+                for _ in 0..nbits {
+                    self.bits.push(value & 1 != 0);
+                    value >>= 1;
+                }
+                bits -= nbits;
+
+                let huffman = self
+                    .huffman
+                    .as_mut()
+                    .expect("huffman should be set up for encoding");
+                for _ in (0..bits).step_by(8) {
+                    huffman.encode_symbol((value & 0xFF) as u8, &mut self.bits);
+                    value >>= 8;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like `MSG_WriteChar`
+    pub fn write_char(&mut self, value: i8) -> Result<(), MessageError> {
+        self.write_bits(value as u8 as u32, 8)
+    }
+
+    /// Like `MSG_WriteByte`
+    pub fn write_byte(&mut self, value: u8) -> Result<(), MessageError> {
+        self.write_bits(value as u32, 8)
+    }
+
+    /// Like `MSG_WriteShort`
+    pub fn write_short(&mut self, value: i16) -> Result<(), MessageError> {
+        self.write_bits(value as u16 as u32, 16)
+    }
+
+    /// Like `MSG_WriteLong`
+    pub fn write_long(&mut self, value: i32) -> Result<(), MessageError> {
+        self.write_bits(value as u32, 32)
+    }
+
+    /// Like `MSG_WriteFloat`
+    pub fn write_float(&mut self, value: f32) -> Result<(), MessageError> {
+        self.write_bits(value.to_bits(), 32)
+    }
+
+    /// Like `MSG_WriteData`
+    pub fn write_data(&mut self, data: impl AsRef<[u8]>) -> Result<(), MessageError> {
+        data.as_ref()
+            .iter()
+            .try_for_each(|&byte| self.write_byte(byte))
+    }
+
+    fn write_string_limited(&mut self, string: &QStr, limit: usize) -> Result<(), MessageError> {
+        if string.len() >= limit {
+            return Err(MessageError::StringTooLong);
+        }
+        self.write_data(string)?;
+        self.write_byte(b'\0')
+    }
+
+    /// Like `MSG_WriteString`
+    ///
+    /// Unlike ioq3 this does not replace `%` and bytes above 127 with `.`,
+    /// and it fails instead of writing an empty string if `string` is too long.
+    pub fn write_string(&mut self, string: &QStr) -> Result<(), MessageError> {
+        self.write_string_limited(string, MAX_STRING_CHARS)
+    }
+
+    /// Like `MSG_WriteBigString`
+    ///
+    /// See [`MessageWriter::write_string`] for differences to ioq3.
+    pub fn write_big_string(&mut self, string: &QStr) -> Result<(), MessageError> {
+        self.write_string_limited(string, BIG_INFO_STRING)
+    }
+
+    /// Finish the message, padding a partially written last byte with zero bits
+    pub fn into_bytes(self) -> Bytes {
+        Bytes::from(self.bits.into_vec())
+    }
+}
+
+/// Incoming message, like `MSG_BeginReading`
+#[derive(Clone, Debug)]
+pub struct MessageReader {
+    data: Bytes,
+    bit: usize,
+    encoding: Encoding,
+    huffman: Option<Box<Huffman>>,
+}
+
+impl MessageReader {
+    pub fn new<T: Into<Bytes>>(data: T, encoding: Encoding) -> Self {
+        let mut reader = Self {
+            data: data.into(),
+            bit: 0,
+            encoding: Encoding::Raw,
+            huffman: None,
+        };
+        reader.set_encoding(encoding);
+        reader
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Switch encoding for all following values, e.g. to [`Encoding::Huffman`] like `MSG_Bitstream`
+    pub fn set_encoding(&mut self, encoding: Encoding) {
+        if Encoding::Huffman == encoding && self.huffman.is_none() {
+            self.huffman = Some(huffman());
+        }
+        self.encoding = encoding;
+    }
+
+    /// Number of bits read so far
+    pub fn bit_position(&self) -> usize {
+        self.bit
+    }
+
+    /// Number of bits left to read
+    pub fn remaining_bits(&self) -> usize {
+        self.data.len() * 8 - self.bit
+    }
+
+    fn bits(&self) -> &BitSlice<u8, Lsb0> {
+        &self.data.as_ref().view_bits::<Lsb0>()[self.bit..]
+    }
+
+    /// Read `bits` into the lower bits of the result, like `MSG_ReadBits` with positive `bits`
+    pub fn read_bits(&mut self, bits: u32) -> Result<u32, MessageError> {
+        check_bits(self.encoding, bits)?;
+
+        let mut value = 0;
+        match self.encoding {
+            Encoding::Raw => {
+                if self.remaining_bits() < bits as usize {
+                    return Err(MessageError::Overflow);
+                }
+                for (i, byte) in self.bits()[..bits as usize].chunks(8).enumerate() {
+                    value |= (byte.load_le::<u8>() as u32) << (i * 8);
+                }
+                self.bit += bits as usize;
+            }
+            Encoding::Huffman => {
+                let nbits = bits & 7;
+                if self.remaining_bits() < nbits as usize {
+                    return Err(MessageError::Overflow);
+                }
+                for i in 0..nbits {
+                    value |= (self.bits()[i as usize] as u32) << i;
+                }
+                self.bit += nbits as usize;
+
+                for i in (nbits..bits).step_by(8) {
+                    let mut huffman = self
+                        .huffman
+                        .take()
+                        .expect("huffman should be set up for decoding");
+                    let decoded = huffman.decode_symbol(self.bits());
+                    self.huffman = Some(huffman);
+                    // the only way for the fixed tree to fail is running out of bits
+                    let (symbol, len) = decoded.map_err(|_e| MessageError::Overflow)?;
+                    value |= (symbol as u32) << i;
+                    self.bit += len;
+                }
+            }
+        }
+
+        Ok(value)
+    }
+
+    /// Read `bits` and sign-extend the result, like `MSG_ReadBits` with negative `bits`
+    pub fn read_signed_bits(&mut self, bits: u32) -> Result<i32, MessageError> {
+        let value = self.read_bits(bits)?;
+        let shift = 32 - bits;
+        Ok(((value << shift) as i32) >> shift)
+    }
+
+    /// Like `MSG_ReadChar`
+    pub fn read_char(&mut self) -> Result<i8, MessageError> {
+        Ok(self.read_bits(8)? as u8 as i8)
+    }
+
+    /// Like `MSG_ReadByte`
+    pub fn read_byte(&mut self) -> Result<u8, MessageError> {
+        Ok(self.read_bits(8)? as u8)
+    }
+
+    /// Like `MSG_ReadShort`
+    pub fn read_short(&mut self) -> Result<i16, MessageError> {
+        Ok(self.read_bits(16)? as u16 as i16)
+    }
+
+    /// Like `MSG_ReadLong`
+    pub fn read_long(&mut self) -> Result<i32, MessageError> {
+        Ok(self.read_bits(32)? as i32)
+    }
+
+    /// Like `MSG_ReadFloat`
+    pub fn read_float(&mut self) -> Result<f32, MessageError> {
+        Ok(f32::from_bits(self.read_bits(32)?))
+    }
+
+    /// Like `MSG_ReadData`
+    pub fn read_data(&mut self, len: usize) -> Result<Bytes, MessageError> {
+        let mut data = Vec::with_capacity(len);
+        for _ in 0..len {
+            data.push(self.read_byte()?);
+        }
+        Ok(data.into())
+    }
+
+    fn read_string_limited(&mut self, limit: usize) -> Result<QString, MessageError> {
+        let mut string = Vec::new();
+        // like the engine this stops at the end of the message and does not consume overlong strings entirely
+        while string.len() < limit - 1 {
+            match self.read_byte() {
+                Err(MessageError::Overflow) | Ok(b'\0') => break,
+                Err(e) => return Err(e),
+                Ok(byte) => string.push(byte),
+            }
+        }
+        // SAFETY: reading stopped at the first NUL
+        Ok(unsafe { QString::from_bytes_unchecked(string) })
+    }
+
+    /// Like `MSG_ReadString`
+    ///
+    /// Unlike ioq3 this does not replace `%` and bytes above 127 with `.`.
+    pub fn read_string(&mut self) -> Result<QString, MessageError> {
+        self.read_string_limited(MAX_STRING_CHARS)
+    }
+
+    /// Like `MSG_ReadBigString`
+    ///
+    /// See [`MessageReader::read_string`] for differences to ioq3.
+    pub fn read_big_string(&mut self) -> Result<QString, MessageError> {
+        self.read_string_limited(BIG_INFO_STRING)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messagewriter_raw() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = MessageWriter::new(Encoding::Raw);
+
+        writer.write_long(-1)?;
+        writer.write_short(0x1234)?;
+        writer.write_byte(0x56)?;
+        assert!(writer.write_bits(0x7, 3).is_err());
+
+        assert_eq!(writer.into_bytes(), &b"\xFF\xFF\xFF\xFF\x34\x12\x56"[..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn messagereader_raw() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reader = MessageReader::new(&b"\xFF\xFF\xFF\xFF\x34\x12\x56"[..], Encoding::Raw);
+
+        assert_eq!(reader.read_long()?, -1);
+        assert_eq!(reader.read_short()?, 0x1234);
+        assert_eq!(reader.read_byte()?, 0x56);
+        assert_eq!(reader.read_byte(), Err(MessageError::Overflow));
+
+        Ok(())
+    }
+
+    #[test]
+    fn messagewriter_huffman() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = MessageWriter::new(Encoding::Huffman);
+
+        writer.write_long(42)?;
+        writer.write_long(7)?;
+        writer.write_long(3)?;
+        writer.write_byte(4)?;
+        writer.write_long(4)?;
+        writer.write_string(QStr::from_bytes(b"score")?)?;
+        writer.write_byte(5)?;
+
+        // see quake3_huffman tests for the fixed tree
+        let expected = hex_literal::hex!(
+            "
+            33 a8 bf ca 56 43 43 d5 bc c7 b3 47 30 23 00
+        "
+        );
+        assert_eq!(writer.into_bytes(), &expected[..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn message_huffman_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = MessageWriter::new(Encoding::Raw);
+        writer.write_long(1337)?;
+        writer.set_encoding(Encoding::Huffman);
+        writer.write_bits(0b101, 3)?;
+        writer.write_bits(0x3FF, 10)?;
+        writer.write_char(-2)?;
+        writer.write_short(-300)?;
+        writer.write_float(13.37)?;
+        writer.write_big_string(QStr::from_bytes(b"\\sv_hostname\\noname")?)?;
+        writer.write_data(b"\xDE\xAD")?;
+        let bytes = writer.into_bytes();
+
+        let mut reader = MessageReader::new(bytes, Encoding::Raw);
+        assert_eq!(reader.read_long()?, 1337);
+        reader.set_encoding(Encoding::Huffman);
+        assert_eq!(reader.read_bits(3)?, 0b101);
+        assert_eq!(reader.read_signed_bits(10)?, -1);
+        assert_eq!(reader.read_char()?, -2);
+        assert_eq!(reader.read_short()?, -300);
+        assert_eq!(reader.read_float()?, 13.37);
+        assert_eq!(
+            reader.read_big_string()?.as_bytes(),
+            b"\\sv_hostname\\noname"
+        );
+        assert_eq!(reader.read_data(2)?, &b"\xDE\xAD"[..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn messagereader_read_string() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reader = MessageReader::new(&b"lorem\0ipsum"[..], Encoding::Raw);
+
+        assert_eq!(reader.read_string()?.as_bytes(), b"lorem");
+        // end of message terminates the string
+        assert_eq!(reader.read_string()?.as_bytes(), b"ipsum");
+        assert_eq!(reader.read_string()?.as_bytes(), b"");
+
+        Ok(())
+    }
+
+    #[test]
+    fn messagewriter_write_string_too_long() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = MessageWriter::new(Encoding::Raw);
+
+        let string = vec![b'a'; MAX_STRING_CHARS];
+        assert_eq!(
+            writer.write_string(QStr::from_bytes(&string)?),
+            Err(MessageError::StringTooLong)
+        );
+        assert!(writer.write_big_string(QStr::from_bytes(&string)?).is_ok());
+
+        Ok(())
+    }
+}
@@ -1 +1,2 @@
 pub mod chan;
+pub mod encode;
@@ -1,3 +1,13 @@
+//! Network channel
+//!
+//! This is the equivalent of `net_chan.c`, which turns messages into sequenced and fragmented packets and back.
+
+use crate::msg::MAX_MSGLEN;
+use crate::{
+    Challenge, FragmentInfo, FragmentStart, NetchanChecksum, PacketSequenceNumber, ProtocolVersion,
+    QPort,
+};
+use bytes::{Bytes, BytesMut};
 use std::ffi::c_int;
 
 pub const MAX_PACKETLEN: usize = 1400;
@@ -5,3 +15,673 @@ pub const MAX_PACKETLEN: usize = 1400;
 pub const FRAGMENT_SIZE: usize = MAX_PACKETLEN - 100;
 
 pub const FRAGMENT_BIT: c_int = 1 << 31;
+
+/// Sequenced packet, i.e. the `SequencedPacket` and `FragmentedPacket` of [`crate::client`] or [`crate::server`]
+pub trait Sequenced: private::Sealed {
+    fn sequence(&self) -> PacketSequenceNumber;
+
+    /// ioq3 checksum, only present for [`ProtocolVersion::IoQuake3`]
+    fn checksum(&self) -> Option<NetchanChecksum>;
+}
+
+impl Sequenced for crate::client::SequencedPacket {
+    fn sequence(&self) -> PacketSequenceNumber {
+        self.sequence()
+    }
+
+    fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum()
+    }
+}
+
+impl Sequenced for crate::server::SequencedPacket {
+    fn sequence(&self) -> PacketSequenceNumber {
+        self.sequence()
+    }
+
+    fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum()
+    }
+}
+
+impl Sequenced for crate::client::FragmentedPacket {
+    fn sequence(&self) -> PacketSequenceNumber {
+        self.sequence()
+    }
+
+    fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum()
+    }
+}
+
+impl Sequenced for crate::server::FragmentedPacket {
+    fn sequence(&self) -> PacketSequenceNumber {
+        self.sequence()
+    }
+
+    fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum()
+    }
+}
+
+/// Fragment of a message, i.e. [`crate::client::FragmentedPacket`] or [`crate::server::FragmentedPacket`]
+pub trait Fragment: Sequenced {
+    fn fragment_info(&self) -> FragmentInfo;
+
+    fn payload(&self) -> &Bytes;
+}
+
+impl Fragment for crate::client::FragmentedPacket {
+    fn fragment_info(&self) -> FragmentInfo {
+        self.fragment_info()
+    }
+
+    fn payload(&self) -> &Bytes {
+        self.payload()
+    }
+}
+
+impl Fragment for crate::server::FragmentedPacket {
+    fn fragment_info(&self) -> FragmentInfo {
+        self.fragment_info()
+    }
+
+    fn payload(&self) -> &Bytes {
+        self.payload()
+    }
+}
+
+/// Error for [`FragmentReassembler`] and [`FragmentQueue`]
+#[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum FragmentError {
+    /// Fragment does not continue the bytes received so far, e.g. because one was lost or duplicated
+    #[error("fragment out of order")]
+    OutOfOrder,
+    /// Message would be longer than [`MAX_MSGLEN`]
+    #[error("message too large")]
+    TooLarge,
+    /// Message is shorter than [`FRAGMENT_SIZE`] and fits into one sequenced packet
+    #[error("message too small")]
+    TooSmall,
+}
+
+/// Collects fragments into a whole message like `Netchan_Process`
+///
+/// Fragments of one message have to arrive in order. A fragment of another sequence starts over,
+/// whereas fragments that are out of order are dropped but keep what has been received so far.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FragmentReassembler {
+    sequence: Option<PacketSequenceNumber>,
+    buffer: BytesMut,
+}
+
+impl Default for FragmentReassembler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FragmentReassembler {
+    pub fn new() -> Self {
+        Self {
+            sequence: None,
+            buffer: BytesMut::new(),
+        }
+    }
+
+    /// Sequence of the message currently being reassembled, if any
+    pub fn sequence(&self) -> Option<PacketSequenceNumber> {
+        self.sequence
+    }
+
+    /// Number of bytes received so far for the current message
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Add a fragment and return the whole message once its last fragment arrived
+    pub fn push<F: Fragment>(&mut self, fragment: &F) -> Result<Option<Bytes>, FragmentError> {
+        let sequence = fragment.sequence();
+        let fragment_info = fragment.fragment_info();
+        let payload = fragment.payload();
+
+        if Some(sequence) != self.sequence {
+            self.sequence = Some(sequence);
+            self.buffer.clear();
+        }
+
+        if usize::from(fragment_info.start()) != self.buffer.len() {
+            return Err(FragmentError::OutOfOrder);
+        }
+
+        if self.buffer.len() + payload.len() > MAX_MSGLEN {
+            return Err(FragmentError::TooLarge);
+        }
+
+        self.buffer.extend_from_slice(payload);
+
+        if !fragment_info.is_last() {
+            return Ok(None);
+        }
+
+        self.sequence = None;
+        Ok(Some(self.buffer.split().freeze()))
+    }
+}
+
+/// Splits a message into fragments like `Netchan_Transmit`
+///
+/// Like the engine, only messages of at least [`FRAGMENT_SIZE`] are fragmented.
+/// Fragments can be sent all at once by iterating, or one per frame like `Netchan_TransmitNextFragment`.
+/// A message that is an exact multiple of [`FRAGMENT_SIZE`] ends with an empty fragment,
+/// so that the other side can tell that no more follow.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct FragmentQueue {
+    sequence: PacketSequenceNumber,
+    message: Bytes,
+    start: usize,
+    finished: bool,
+    checksum_challenge: Option<Challenge>,
+}
+
+impl FragmentQueue {
+    pub fn new<T: Into<Bytes>>(
+        sequence: PacketSequenceNumber,
+        message: T,
+    ) -> Result<Self, FragmentError> {
+        let message: Bytes = message.into();
+        if message.len() > MAX_MSGLEN {
+            return Err(FragmentError::TooLarge);
+        }
+        if message.len() < FRAGMENT_SIZE {
+            return Err(FragmentError::TooSmall);
+        }
+        Ok(Self {
+            sequence,
+            message,
+            start: 0,
+            finished: false,
+            checksum_challenge: None,
+        })
+    }
+
+    /// Add a [`crate::NetchanChecksum`] of `challenge` to each fragment
+    pub fn with_checksum(mut self, challenge: Challenge) -> Self {
+        self.checksum_challenge = Some(challenge);
+        self
+    }
+
+    pub fn sequence(&self) -> PacketSequenceNumber {
+        self.sequence
+    }
+
+    /// Challenge for the checksum of each fragment, if any
+    pub fn checksum_challenge(&self) -> Option<Challenge> {
+        self.checksum_challenge
+    }
+
+    /// Whether all fragments have been sent, i.e. the opposite of `unsentFragments`
+    pub fn is_empty(&self) -> bool {
+        self.finished
+    }
+
+    /// Take the next fragment to send
+    pub fn next_fragment(&mut self) -> Option<(FragmentStart, Bytes)> {
+        if self.finished {
+            return None;
+        }
+
+        let start = self.start;
+        let length = FRAGMENT_SIZE.min(self.message.len() - start);
+        let payload = self.message.slice(start..start + length);
+        self.start += length;
+
+        // an exact multiple of FRAGMENT_SIZE needs another, empty fragment
+        if self.start == self.message.len() && length != FRAGMENT_SIZE {
+            self.finished = true;
+        }
+
+        let start = start.try_into().expect("start should be within MAX_MSGLEN");
+        Some((start, payload))
+    }
+}
+
+impl Iterator for FragmentQueue {
+    type Item = (FragmentStart, Bytes);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_fragment()
+    }
+}
+
+/// Error for [`Netchan`]
+#[derive(thiserror::Error, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum NetchanError {
+    /// Packet is not newer than the last one, i.e. out of order or duplicated
+    #[error("stale sequence")]
+    Stale,
+    /// Checksum does not match the challenge of the connection, or is missing or unexpected
+    #[error("invalid checksum")]
+    InvalidChecksum,
+    #[error(transparent)]
+    Fragment(#[from] FragmentError),
+}
+
+/// State of a network channel like `netchan_t`
+///
+/// Outgoing sequences start at 1 like in `Netchan_Setup`, so that the first incoming packet is accepted.
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Netchan {
+    qport: QPort,
+    protocol: ProtocolVersion,
+    challenge: Challenge,
+    incoming_sequence: PacketSequenceNumber,
+    outgoing_sequence: PacketSequenceNumber,
+    dropped: u32,
+    reassembler: FragmentReassembler,
+}
+
+impl Netchan {
+    /// Channel for [`ProtocolVersion::Quake3`]
+    pub fn new(qport: QPort) -> Self {
+        Self::with_protocol(qport, ProtocolVersion::Quake3, Challenge::default())
+    }
+
+    /// Channel for `protocol`, with the `challenge` of the connection for checksums
+    pub fn with_protocol(qport: QPort, protocol: ProtocolVersion, challenge: Challenge) -> Self {
+        Self {
+            qport,
+            protocol,
+            challenge,
+            incoming_sequence: PacketSequenceNumber(0),
+            outgoing_sequence: PacketSequenceNumber(1),
+            dropped: 0,
+            reassembler: FragmentReassembler::new(),
+        }
+    }
+
+    pub fn qport(&self) -> QPort {
+        self.qport
+    }
+
+    pub fn protocol(&self) -> ProtocolVersion {
+        self.protocol
+    }
+
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+
+    /// Challenge for the checksum of sequenced packets, if the protocol has one
+    pub fn checksum_challenge(&self) -> Option<Challenge> {
+        self.protocol.has_checksum().then_some(self.challenge)
+    }
+
+    /// Sequence of the last whole incoming message
+    pub fn incoming_sequence(&self) -> PacketSequenceNumber {
+        self.incoming_sequence
+    }
+
+    /// Sequence of the next outgoing message
+    pub fn outgoing_sequence(&self) -> PacketSequenceNumber {
+        self.outgoing_sequence
+    }
+
+    /// Number of packets dropped before the last processed one
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    fn check_checksum<P: Sequenced>(&self, packet: &P) -> Result<(), NetchanError> {
+        let checksum = self
+            .checksum_challenge()
+            .map(|challenge| NetchanChecksum::new(challenge, packet.sequence()));
+        if packet.checksum() != checksum {
+            return Err(NetchanError::InvalidChecksum);
+        }
+        Ok(())
+    }
+
+    fn check_incoming(&mut self, sequence: PacketSequenceNumber) -> Result<(), NetchanError> {
+        if !sequence.is_after(self.incoming_sequence) {
+            return Err(NetchanError::Stale);
+        }
+        self.dropped = sequence.wrapping_distance(self.incoming_sequence) - 1;
+        Ok(())
+    }
+
+    /// Process an incoming unfragmented packet like `Netchan_Process`
+    ///
+    /// Returns the number of packets that were dropped in between.
+    /// For [`ProtocolVersion::IoQuake3`] the checksum has to match the challenge.
+    pub fn process<P: Sequenced>(&mut self, packet: &P) -> Result<u32, NetchanError> {
+        self.check_checksum(packet)?;
+        let sequence = packet.sequence();
+        self.check_incoming(sequence)?;
+        self.incoming_sequence = sequence;
+        Ok(self.dropped)
+    }
+
+    /// Process an incoming fragment like `Netchan_Process`
+    ///
+    /// Returns the whole message once its last fragment arrived.
+    /// The incoming sequence only advances with a whole message.
+    /// For [`ProtocolVersion::IoQuake3`] the checksum has to match the challenge.
+    pub fn process_fragment<F: Fragment>(
+        &mut self,
+        fragment: &F,
+    ) -> Result<Option<Bytes>, NetchanError> {
+        self.check_checksum(fragment)?;
+        self.check_incoming(fragment.sequence())?;
+        let message = self.reassembler.push(fragment)?;
+        if message.is_some() {
+            self.incoming_sequence = fragment.sequence();
+        }
+        Ok(message)
+    }
+
+    /// Take the sequence for the next outgoing message like `Netchan_Transmit`
+    pub fn transmit(&mut self) -> PacketSequenceNumber {
+        let sequence = self.outgoing_sequence;
+        self.outgoing_sequence = sequence.next();
+        sequence
+    }
+
+    /// Fragment the next outgoing message like `Netchan_Transmit`
+    ///
+    /// The outgoing sequence advances right away, so all fragments have to be sent before the next message.
+    /// Messages shorter than [`FRAGMENT_SIZE`] are rejected, those are sent with [`Netchan::transmit`] instead.
+    pub fn transmit_fragments<T: Into<Bytes>>(
+        &mut self,
+        message: T,
+    ) -> Result<FragmentQueue, FragmentError> {
+        let mut queue = FragmentQueue::new(self.outgoing_sequence, message)?;
+        self.outgoing_sequence = self.outgoing_sequence.next();
+        queue.checksum_challenge = self.checksum_challenge();
+        Ok(queue)
+    }
+}
+
+mod private {
+    pub trait Sealed {}
+
+    impl Sealed for crate::client::SequencedPacket {}
+    impl Sealed for crate::server::SequencedPacket {}
+    impl Sealed for crate::client::FragmentedPacket {}
+    impl Sealed for crate::server::FragmentedPacket {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::{FragmentedPacket, SequencedPacket};
+    use crate::QPort;
+
+    fn sequenced(sequence: c_int) -> Result<SequencedPacket, Box<dyn std::error::Error>> {
+        Ok(SequencedPacket::new(
+            PacketSequenceNumber::new(sequence)?,
+            QPort::new(27960)?,
+            &b"payload"[..],
+        )?)
+    }
+
+    fn fragment(
+        sequence: c_int,
+        start: usize,
+        payload: Vec<u8>,
+    ) -> Result<FragmentedPacket, Box<dyn std::error::Error>> {
+        Ok(FragmentedPacket::new(
+            PacketSequenceNumber::new(sequence)?,
+            QPort::new(27960)?,
+            start.try_into()?,
+            payload,
+        )?)
+    }
+
+    #[test]
+    fn fragmentreassembler_push() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reassembler = FragmentReassembler::new();
+
+        let first = fragment(42, 0, vec![0xAA; FRAGMENT_SIZE])?;
+        assert_eq!(reassembler.push(&first)?, None);
+        assert_eq!(reassembler.len(), FRAGMENT_SIZE);
+
+        // duplicate
+        assert_eq!(reassembler.push(&first), Err(FragmentError::OutOfOrder));
+
+        // out of order
+        let third = fragment(42, FRAGMENT_SIZE * 2, vec![0xCC; 3])?;
+        assert_eq!(reassembler.push(&third), Err(FragmentError::OutOfOrder));
+        assert_eq!(reassembler.len(), FRAGMENT_SIZE);
+
+        let second = fragment(42, FRAGMENT_SIZE, vec![0xBB; 3])?;
+        let message = reassembler
+            .push(&second)?
+            .expect("message should be complete");
+        assert_eq!(message.len(), FRAGMENT_SIZE + 3);
+        assert_eq!(&message[FRAGMENT_SIZE..], &[0xBB; 3][..]);
+
+        assert!(reassembler.is_empty());
+        assert_eq!(reassembler.sequence(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentreassembler_push_new_sequence() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reassembler = FragmentReassembler::new();
+
+        reassembler.push(&fragment(42, 0, vec![0xAA; FRAGMENT_SIZE])?)?;
+
+        // a new sequence starts over, so its first fragment is accepted
+        reassembler.push(&fragment(43, 0, vec![0xBB; FRAGMENT_SIZE])?)?;
+        assert_eq!(reassembler.sequence(), Some(PacketSequenceNumber::new(43)?));
+        assert_eq!(reassembler.len(), FRAGMENT_SIZE);
+
+        // a zero-length fragment terminates a message that is a multiple of FRAGMENT_SIZE
+        let message = reassembler
+            .push(&fragment(43, FRAGMENT_SIZE, vec![])?)?
+            .expect("message should be complete");
+        assert_eq!(message.len(), FRAGMENT_SIZE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentreassembler_push_too_large() -> Result<(), Box<dyn std::error::Error>> {
+        let mut reassembler = FragmentReassembler::new();
+
+        let count = MAX_MSGLEN / FRAGMENT_SIZE;
+        for i in 0..count {
+            reassembler.push(&fragment(42, i * FRAGMENT_SIZE, vec![0; FRAGMENT_SIZE])?)?;
+        }
+
+        let remaining = MAX_MSGLEN - count * FRAGMENT_SIZE;
+        assert_eq!(
+            reassembler.push(&fragment(
+                42,
+                count * FRAGMENT_SIZE,
+                vec![0; remaining + 1]
+            )?),
+            Err(FragmentError::TooLarge)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentqueue_next_fragment() -> Result<(), Box<dyn std::error::Error>> {
+        let message = vec![0xAA; FRAGMENT_SIZE * 2 + 3];
+        let mut queue = FragmentQueue::new(PacketSequenceNumber::new(42)?, message)?;
+
+        let (start, payload) = queue.next_fragment().expect("first fragment");
+        assert_eq!(usize::from(start), 0);
+        assert_eq!(payload.len(), FRAGMENT_SIZE);
+        assert!(!queue.is_empty());
+
+        let (start, payload) = queue.next_fragment().expect("second fragment");
+        assert_eq!(usize::from(start), FRAGMENT_SIZE);
+        assert_eq!(payload.len(), FRAGMENT_SIZE);
+
+        let (start, payload) = queue.next_fragment().expect("last fragment");
+        assert_eq!(usize::from(start), FRAGMENT_SIZE * 2);
+        assert_eq!(payload.len(), 3);
+
+        assert!(queue.is_empty());
+        assert_eq!(queue.next_fragment(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentqueue_exact_multiple() -> Result<(), Box<dyn std::error::Error>> {
+        let message = vec![0xAA; FRAGMENT_SIZE * 2];
+        let queue = FragmentQueue::new(PacketSequenceNumber::new(42)?, message)?;
+
+        let fragments: Vec<_> = queue.collect();
+        assert_eq!(fragments.len(), 3);
+        assert_eq!(usize::from(fragments[2].0), FRAGMENT_SIZE * 2);
+        assert!(fragments[2].1.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentqueue_too_large() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(
+            FragmentQueue::new(PacketSequenceNumber::new(42)?, vec![0; MAX_MSGLEN + 1]),
+            Err(FragmentError::TooLarge)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentqueue_too_small() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(
+            FragmentQueue::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE - 1]),
+            Err(FragmentError::TooSmall)
+        );
+        assert!(FragmentQueue::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE]).is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentqueue_reassemble() -> Result<(), Box<dyn std::error::Error>> {
+        let message: Vec<u8> = (0..FRAGMENT_SIZE * 3).map(|i| i as u8).collect();
+        let mut queue = FragmentQueue::new(PacketSequenceNumber::new(42)?, message.clone())?;
+        let mut reassembler = FragmentReassembler::new();
+
+        let mut reassembled = None;
+        while let Some(packet) = FragmentedPacket::next_from(&mut queue, QPort::new(27960)?) {
+            reassembled = reassembler.push(&packet)?;
+        }
+        assert_eq!(reassembled, Some(Bytes::from(message)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn netchan_process() -> Result<(), Box<dyn std::error::Error>> {
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+
+        assert_eq!(netchan.process(&sequenced(1)?)?, 0);
+        assert_eq!(netchan.process(&sequenced(4)?)?, 2);
+        assert_eq!(netchan.dropped(), 2);
+        assert_eq!(netchan.incoming_sequence(), PacketSequenceNumber::new(4)?);
+
+        // duplicate
+        assert_eq!(netchan.process(&sequenced(4)?), Err(NetchanError::Stale));
+        // out of order
+        assert_eq!(netchan.process(&sequenced(3)?), Err(NetchanError::Stale));
+        assert_eq!(netchan.incoming_sequence(), PacketSequenceNumber::new(4)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn netchan_process_checksum() -> Result<(), Box<dyn std::error::Error>> {
+        // NETCHAN_GENCHECKSUM is always 0 for sequence 1, so start at 2
+        let challenge = Challenge::new(0x12345678);
+        let mut netchan =
+            Netchan::with_protocol(QPort::new(27960)?, ProtocolVersion::IoQuake3, challenge);
+
+        assert_eq!(
+            netchan.process(&sequenced(2)?.with_checksum(Challenge::new(42))),
+            Err(NetchanError::InvalidChecksum)
+        );
+        assert_eq!(
+            netchan.process(&sequenced(2)?),
+            Err(NetchanError::InvalidChecksum)
+        );
+        assert_eq!(
+            netchan
+                .process_fragment(&fragment(2, 0, vec![0; 1])?.with_checksum(Challenge::new(42))),
+            Err(NetchanError::InvalidChecksum)
+        );
+        assert_eq!(netchan.incoming_sequence(), PacketSequenceNumber::new(0)?);
+
+        assert_eq!(netchan.process(&sequenced(2)?.with_checksum(challenge))?, 1);
+        assert_eq!(
+            netchan.process_fragment(&fragment(3, 0, vec![0; 1])?.with_checksum(challenge))?,
+            Some(Bytes::from(vec![0; 1]))
+        );
+
+        // protocol 68 has no checksum at all
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+        assert_eq!(
+            netchan.process(&sequenced(2)?.with_checksum(challenge)),
+            Err(NetchanError::InvalidChecksum)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn netchan_process_fragment() -> Result<(), Box<dyn std::error::Error>> {
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+
+        assert_eq!(
+            netchan.process_fragment(&fragment(2, 0, vec![0; FRAGMENT_SIZE])?)?,
+            None
+        );
+        assert_eq!(netchan.dropped(), 1);
+        assert_eq!(netchan.incoming_sequence(), PacketSequenceNumber::new(0)?);
+
+        let message = netchan.process_fragment(&fragment(2, FRAGMENT_SIZE, vec![0; 1])?)?;
+        assert_eq!(message.map(|m| m.len()), Some(FRAGMENT_SIZE + 1));
+        assert_eq!(netchan.incoming_sequence(), PacketSequenceNumber::new(2)?);
+
+        assert_eq!(
+            netchan.process_fragment(&fragment(2, FRAGMENT_SIZE, vec![0; 1])?),
+            Err(NetchanError::Stale)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn netchan_transmit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+
+        assert_eq!(netchan.transmit(), PacketSequenceNumber::new(1)?);
+
+        let queue = netchan.transmit_fragments(vec![0; FRAGMENT_SIZE])?;
+        assert_eq!(queue.sequence(), PacketSequenceNumber::new(2)?);
+
+        // a short message is sent as one sequenced packet and does not take a sequence here
+        assert_eq!(
+            netchan.transmit_fragments(vec![0; FRAGMENT_SIZE - 1]),
+            Err(FragmentError::TooSmall)
+        );
+
+        assert_eq!(netchan.outgoing_sequence(), PacketSequenceNumber::new(3)?);
+
+        Ok(())
+    }
+}
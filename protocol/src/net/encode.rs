@@ -0,0 +1,326 @@
+//! Message scrambling of [`crate::ProtocolVersion::Quake3`]
+//!
+//! This is the equivalent of `CL_Netchan_Encode` and friends in `cl_net_chan.c` and `sv_net_chan.c`,
+//! which XOR the payload of sequenced packets with a key derived from the connection state.
+//! Since XOR is its own inverse, encoding and decoding the same message take the same key.
+
+use crate::msg::{Encoding, MessageError, MessageReader};
+use crate::{Challenge, PacketSequenceNumber};
+use bytes::Bytes;
+use std::ffi::c_int;
+
+/// Number of reliable commands kept for acknowledgement
+pub const MAX_RELIABLE_COMMANDS: usize = 64;
+
+// serverId, messageAcknowledge and reliableAcknowledge
+const CLIENT_START: usize = 12;
+// reliableAcknowledge
+const SERVER_START: usize = 4;
+
+/// Reliable commands by sequence, like `clc.reliableCommands` or `client->reliableCommands`
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
+pub struct ReliableCommands {
+    commands: [Bytes; MAX_RELIABLE_COMMANDS],
+}
+
+impl Default for ReliableCommands {
+    fn default() -> Self {
+        Self {
+            commands: std::array::from_fn(|_| Bytes::new()),
+        }
+    }
+}
+
+impl ReliableCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Command with `sequence`, or the one that overwrote it
+    pub fn get(&self, sequence: c_int) -> &[u8] {
+        &self.commands[Self::index(sequence)]
+    }
+
+    pub fn insert<T: Into<Bytes>>(&mut self, sequence: c_int, command: T) {
+        self.commands[Self::index(sequence)] = command.into();
+    }
+
+    fn index(sequence: c_int) -> usize {
+        sequence as usize & (MAX_RELIABLE_COMMANDS - 1)
+    }
+}
+
+fn scramble(message: &mut [u8], start: usize, mut key: u8, command: &[u8]) {
+    // the engine stops at the terminating `\0`
+    let command = match command.iter().position(|&c| c == 0) {
+        Some(len) => &command[..len],
+        None => command,
+    };
+
+    let mut index = 0;
+    for (i, byte) in message.iter_mut().enumerate().skip(start) {
+        if index >= command.len() {
+            index = 0;
+        }
+        let c = match command.get(index) {
+            Some(&c) if c <= 127 && c != b'%' => c,
+            Some(_) => b'.',
+            None => 0,
+        };
+        key ^= c << (i & 1);
+        index += 1;
+
+        *byte ^= key;
+    }
+}
+
+fn scramble_client_message(
+    message: &mut [u8],
+    challenge: Challenge,
+    commands: &ReliableCommands,
+) -> Result<(), MessageError> {
+    if message.len() <= CLIENT_START {
+        return Ok(());
+    }
+
+    let mut reader = MessageReader::new(Bytes::copy_from_slice(message), Encoding::Huffman);
+    let server_id = reader.read_long()?;
+    let message_acknowledge = reader.read_long()?;
+    let reliable_acknowledge = reader.read_long()?;
+
+    let key = c_int::from(challenge) ^ server_id ^ message_acknowledge;
+    scramble(
+        message,
+        CLIENT_START,
+        key as u8,
+        commands.get(reliable_acknowledge),
+    );
+    Ok(())
+}
+
+fn server_message_key(challenge: Challenge, sequence: PacketSequenceNumber) -> u8 {
+    (c_int::from(challenge) ^ c_int::from(sequence)) as u8
+}
+
+/// Encode an outgoing client message like `CL_Netchan_Encode`
+///
+/// `message` is the payload of a sequenced packet, before fragmentation.
+/// The acknowledged command is looked up in `server_commands` like `clc.serverCommands`.
+pub fn encode_client_message(
+    message: &mut [u8],
+    challenge: Challenge,
+    server_commands: &ReliableCommands,
+) -> Result<(), MessageError> {
+    scramble_client_message(message, challenge, server_commands)
+}
+
+/// Decode an incoming client message like `SV_Netchan_Decode`
+///
+/// `message` is the payload of a sequenced packet, after reassembly.
+/// The acknowledged command is looked up in `reliable_commands` like `client->reliableCommands`.
+pub fn decode_client_message(
+    message: &mut [u8],
+    challenge: Challenge,
+    reliable_commands: &ReliableCommands,
+) -> Result<(), MessageError> {
+    scramble_client_message(message, challenge, reliable_commands)
+}
+
+/// Encode an outgoing server message like `SV_Netchan_Encode`
+///
+/// `message` is the payload of the packet with `sequence`, before fragmentation.
+/// `client_command` is the last command received from the client, like `client->lastClientCommandString`.
+pub fn encode_server_message(
+    message: &mut [u8],
+    challenge: Challenge,
+    sequence: PacketSequenceNumber,
+    client_command: &[u8],
+) {
+    if message.len() < SERVER_START {
+        return;
+    }
+
+    scramble(
+        message,
+        SERVER_START,
+        server_message_key(challenge, sequence),
+        client_command,
+    );
+}
+
+/// Decode an incoming server message like `CL_Netchan_Decode`
+///
+/// `message` is the payload of the packet with `sequence`, after reassembly.
+/// The acknowledged command is looked up in `reliable_commands` like `clc.reliableCommands`.
+pub fn decode_server_message(
+    message: &mut [u8],
+    challenge: Challenge,
+    sequence: PacketSequenceNumber,
+    reliable_commands: &ReliableCommands,
+) -> Result<(), MessageError> {
+    let mut reader = MessageReader::new(Bytes::copy_from_slice(message), Encoding::Huffman);
+    let reliable_acknowledge = reader.read_long()?;
+
+    scramble(
+        message,
+        SERVER_START,
+        server_message_key(challenge, sequence),
+        reliable_commands.get(reliable_acknowledge),
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::MessageWriter;
+
+    #[test]
+    fn reliablecommands_get() {
+        let mut commands = ReliableCommands::new();
+        assert_eq!(commands.get(3), b"");
+
+        commands.insert(3, &b"say hi"[..]);
+        assert_eq!(commands.get(3), b"say hi");
+        assert_eq!(commands.get(3 + MAX_RELIABLE_COMMANDS as c_int), b"say hi");
+    }
+
+    #[test]
+    fn scramble_key() {
+        let mut message = [0u8; 6];
+        scramble(&mut message, 2, 0x01, b"a%\xFF\0ignored");
+
+        // 'a' << 0, '.' << 1, '.' << 0, then around to 'a' << 1
+        let mut key = 0x01u8;
+        let mut expected = [0u8; 6];
+        for (i, c) in [(2, b'a'), (3, b'.'), (4, b'.'), (5, b'a')] {
+            key ^= c << (i & 1);
+            expected[i] = key;
+        }
+        assert_eq!(message, expected);
+    }
+
+    #[test]
+    fn client_message_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = MessageWriter::new(Encoding::Huffman);
+        writer.write_long(1337)?;
+        writer.write_long(42)?;
+        writer.write_long(7)?;
+        writer.write_string(quake3::qstr::QStr::from_bytes(b"userinfo")?)?;
+        let original = writer.into_bytes().to_vec();
+
+        let challenge = Challenge::new(0x12345678);
+        let mut commands = ReliableCommands::new();
+        commands.insert(7, &b"cs 1 \"\""[..]);
+
+        let mut message = original.clone();
+        encode_client_message(&mut message, challenge, &commands)?;
+        assert_eq!(message[..CLIENT_START], original[..CLIENT_START]);
+        assert_ne!(message, original);
+
+        decode_client_message(&mut message, challenge, &commands)?;
+        assert_eq!(message, original);
+
+        Ok(())
+    }
+
+    // serverId 1337, messageAcknowledge 42, reliableAcknowledge 7,
+    // then clc_clientCommand 8 "say \"hello there\"" and clc_EOF, Huffman encoded
+    const CLIENT_MESSAGE: [u8; 26] = hex_literal::hex!(
+        "
+        f1 22 74 06 f5 57 43 ab 9a c7 13 7e 13 e1 60 98
+        98 b3 77 96 83 1d c1 88 18 01
+    "
+    );
+
+    // TODO: replace with a packet captured from a protocol 68 client
+    // computed by hand following CL_Netchan_Encode, with key = challenge ^ 1337 ^ 42 and "cs 5 %\xE9"
+    const CLIENT_MESSAGE_ENCODED: [u8; 26] = hex_literal::hex!(
+        "
+        f1 22 74 06 f5 57 43 ab 9a c7 13 7e db cf 6e fc
+        dc ab 41 66 00 de 37 3e 80 c5
+    "
+    );
+
+    #[test]
+    fn client_message_vector() -> Result<(), Box<dyn std::error::Error>> {
+        let challenge = Challenge::new(-9938504);
+        let mut commands = ReliableCommands::new();
+        commands.insert(7, &b"cs 5 %\xE9"[..]);
+
+        let mut message = CLIENT_MESSAGE;
+        encode_client_message(&mut message, challenge, &commands)?;
+        assert_eq!(message, CLIENT_MESSAGE_ENCODED);
+
+        decode_client_message(&mut message, challenge, &commands)?;
+        assert_eq!(message, CLIENT_MESSAGE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn client_message_short() -> Result<(), Box<dyn std::error::Error>> {
+        let mut message = [0xAA; CLIENT_START];
+        encode_client_message(&mut message, Challenge::new(1), &ReliableCommands::new())?;
+        assert_eq!(message, [0xAA; CLIENT_START]);
+
+        Ok(())
+    }
+
+    // reliableAcknowledge 7, then svc_serverCommand 3 "print \"100%\"" and svc_EOF, Huffman encoded
+    const SERVER_MESSAGE: [u8; 17] = hex_literal::hex!(
+        "
+        bf 1a c1 56 0b 8f 50 18 b2 37 91 e1 d3 29 7f 22
+        56
+    "
+    );
+
+    // TODO: replace with a packet captured from a protocol 68 server
+    // computed by hand following SV_Netchan_Encode, with key = challenge ^ 5 and "say 50%\xE9"
+    const SERVER_MESSAGE_ENCODED: [u8; 17] = hex_literal::hex!(
+        "
+        bf 1a c1 56 c5 83 25 2d b2 57 df f3 b2 8a a5 b8
+        f9
+    "
+    );
+
+    #[test]
+    fn server_message_vector() -> Result<(), Box<dyn std::error::Error>> {
+        let challenge = Challenge::new(-9938504);
+        let sequence = PacketSequenceNumber::new(5)?;
+        let mut commands = ReliableCommands::new();
+        commands.insert(7, &b"say 50%\xE9"[..]);
+
+        let mut message = SERVER_MESSAGE;
+        encode_server_message(&mut message, challenge, sequence, b"say 50%\xE9");
+        assert_eq!(message, SERVER_MESSAGE_ENCODED);
+
+        decode_server_message(&mut message, challenge, sequence, &commands)?;
+        assert_eq!(message, SERVER_MESSAGE);
+
+        Ok(())
+    }
+
+    #[test]
+    fn server_message_roundtrip() -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = MessageWriter::new(Encoding::Huffman);
+        writer.write_long(7)?;
+        writer.write_data([0xDE, 0xAD, 0xBE, 0xEF, 0x00, 0x2A])?;
+        let original = writer.into_bytes().to_vec();
+
+        let challenge = Challenge::new(0x12345678);
+        let sequence = PacketSequenceNumber::new(3)?;
+        let mut commands = ReliableCommands::new();
+        commands.insert(7, &b"say 100%"[..]);
+
+        let mut message = original.clone();
+        encode_server_message(&mut message, challenge, sequence, b"say 100%");
+        assert_eq!(message[..SERVER_START], original[..SERVER_START]);
+        assert_ne!(message, original);
+
+        decode_server_message(&mut message, challenge, sequence, &commands)?;
+        assert_eq!(message, original);
+
+        Ok(())
+    }
+}
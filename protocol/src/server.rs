@@ -13,35 +13,48 @@
 //! Packets from and to master servers and auth server are always connectionless.
 //!
 //! A connectionless outer packet contains an inner message of [`ConnectionlessMessage`]:
-//! - TODO: `GetStatusMessage`
-//! - TODO: `GetInfoMessage`
-//! - TODO: `GetChallengeMessage`
+//! - [`GetStatusMessage`]
+//! - [`GetInfoMessage`]
+//! - [`GetChallengeMessage`]
 //! - [`ConnectMessage`]
-//! - TODO:  `IpAuthorizeMessage`
+//! - [`DisconnectMessage`]
+//! - [`IpAuthorizeMessage`]
+//! - [`RconMessage`]
+//! - [`HeartbeatMessage`]
+//! - [`GetServersMessage`]
+//! - [`GetServersExtMessage`]
 
 pub use super::ConnectionlessPacket;
 
 use super::{
-    FragmentInfo, FragmentLength, FragmentStart, InvalidConnectionlessPacketError,
-    InvalidFragmentLengthError, InvalidFragmentStartError, InvalidQPortError, PacketKind,
-    PacketSequenceNumber, QPort,
+    Challenge, FragmentInfo, FragmentLength, FragmentStart, InvalidConnectionlessPacketError,
+    InvalidFragmentLengthError, InvalidFragmentStartError, InvalidQPortError, NetchanChecksum,
+    PacketKind, PacketSequence, PacketSequenceNumber, ProtocolVersion, QPort, UnknownCommandError,
 };
-use crate::net::chan::FRAGMENT_SIZE;
+use crate::cmd;
+use crate::net::chan::{FragmentQueue, Netchan, FRAGMENT_SIZE};
 use bytes::BytesMut;
-use bytes::{Buf, Bytes};
+use bytes::{Buf, BufMut, Bytes};
+use quake3::info::InfoKv;
 use quake3::info::InfoMap;
 use quake3::info::InfoString;
 use quake3::info::INFO_LIMIT;
+use quake3::qstr::{QStr, QString};
+use std::ffi::c_int;
 use winnow::binary::le_u16;
-use winnow::combinator::delimited;
-use winnow::combinator::rest;
 use winnow::combinator::seq;
 use winnow::error::ContextError;
-use winnow::token::literal;
-use winnow::token::take_until;
-use winnow::PResult;
+use winnow::token::rest;
+use winnow::token::take;
 use winnow::Parser;
 
+// sequence
+const HEADER_LEN: usize = core::mem::size_of::<i32>();
+// optional ioq3 checksum
+const CHECKSUM_LEN: usize = core::mem::size_of::<i32>();
+// fragment start and length
+const FRAGMENT_HEADER_LEN: usize = 2 * core::mem::size_of::<u16>();
+
 /// Error for invalid [`SequencedPacket`]
 #[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[error("is invalid")]
@@ -53,7 +66,7 @@ pub struct InvalidSequencedPacketError {
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct SequencedPacket {
     sequence: PacketSequenceNumber,
-    // TODO: ioq3 has additional checksum
+    checksum: Option<NetchanChecksum>,
     payload: Bytes,
 }
 
@@ -67,17 +80,65 @@ impl SequencedPacket {
         if payload.len() >= FRAGMENT_SIZE {
             Err(InvalidSequencedPacketError { payload })
         } else {
-            Ok(Self { sequence, payload })
+            Ok(Self {
+                sequence,
+                checksum: None,
+                payload,
+            })
+        }
+    }
+
+    /// Wrap `payload` into the next outgoing packet of `netchan` like `Netchan_Transmit`
+    pub fn transmit<T: Into<Bytes>>(
+        netchan: &mut Netchan,
+        payload: T,
+    ) -> Result<Self, InvalidSequencedPacketError> {
+        let payload: Bytes = payload.into();
+        if payload.len() >= FRAGMENT_SIZE {
+            return Err(InvalidSequencedPacketError { payload });
         }
+        let packet = Self::new(netchan.transmit(), payload)?;
+        Ok(packet.with_checksum_opt(netchan.checksum_challenge()))
     }
 
     pub fn sequence(&self) -> PacketSequenceNumber {
         self.sequence
     }
 
+    /// ioq3 checksum, only present for [`ProtocolVersion::IoQuake3`]
+    pub fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum
+    }
+
+    /// Add the ioq3 checksum for `challenge`
+    pub fn with_checksum(mut self, challenge: Challenge) -> Self {
+        self.checksum = Some(NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
+    /// Add the ioq3 checksum if there is a `challenge`, like [`Netchan::checksum_challenge`]
+    pub(crate) fn with_checksum_opt(mut self, challenge: Option<Challenge>) -> Self {
+        self.checksum = challenge.map(|challenge| NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
     pub fn payload(&self) -> &Bytes {
         &self.payload
     }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        PacketSequence::new_with_number_and_fragment(self.sequence, false).write_to(buf);
+        if let Some(checksum) = self.checksum {
+            checksum.write_to(buf);
+        }
+        buf.put_slice(&self.payload);
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(HEADER_LEN + CHECKSUM_LEN + self.payload.len());
+        self.write_to(&mut buf);
+        buf.freeze()
+    }
 }
 
 /// Error for invalid [`FragmentedPacket`]
@@ -91,7 +152,7 @@ pub struct InvalidFragmentedPacketError {
 #[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct FragmentedPacket {
     sequence: PacketSequenceNumber,
-    // TODO: ioq3 has additional checksum
+    checksum: Option<NetchanChecksum>,
     fragment_info: FragmentInfo,
     payload: Bytes,
 }
@@ -109,6 +170,7 @@ impl FragmentedPacket {
             Err(_) => Err(InvalidFragmentedPacketError { payload }),
             Ok(fragment_length) => Ok(Self {
                 sequence,
+                checksum: None,
                 fragment_info: FragmentInfo::new(fragment_start, fragment_length),
                 payload,
             }),
@@ -127,9 +189,51 @@ impl FragmentedPacket {
         self.fragment_info.is_last()
     }
 
+    /// ioq3 checksum, only present for [`ProtocolVersion::IoQuake3`]
+    pub fn checksum(&self) -> Option<NetchanChecksum> {
+        self.checksum
+    }
+
+    /// Add the ioq3 checksum for `challenge`
+    pub fn with_checksum(mut self, challenge: Challenge) -> Self {
+        self.checksum = Some(NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
+    /// Add the ioq3 checksum if there is a `challenge`, like [`Netchan::checksum_challenge`]
+    pub(crate) fn with_checksum_opt(mut self, challenge: Option<Challenge>) -> Self {
+        self.checksum = challenge.map(|challenge| NetchanChecksum::new(challenge, self.sequence));
+        self
+    }
+
     pub fn payload(&self) -> &Bytes {
         &self.payload
     }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        PacketSequence::new_with_number_and_fragment(self.sequence, true).write_to(buf);
+        if let Some(checksum) = self.checksum {
+            checksum.write_to(buf);
+        }
+        self.fragment_info.write_to(buf);
+        buf.put_slice(&self.payload);
+    }
+
+    pub fn to_bytes(&self) -> Bytes {
+        let mut buf = BytesMut::with_capacity(
+            HEADER_LEN + CHECKSUM_LEN + FRAGMENT_HEADER_LEN + self.payload.len(),
+        );
+        self.write_to(&mut buf);
+        buf.freeze()
+    }
+
+    /// Take the next fragment of `queue`, like `Netchan_TransmitNextFragment`
+    pub fn next_from(queue: &mut FragmentQueue) -> Option<Self> {
+        let (start, payload) = queue.next_fragment()?;
+        let packet = Self::new(queue.sequence(), start, payload)
+            .expect("fragment should be at most FRAGMENT_SIZE");
+        Some(packet.with_checksum_opt(queue.checksum_challenge()))
+    }
 }
 
 /// Incoming packet
@@ -153,6 +257,7 @@ pub enum InvalidPacketError {
     InvalidFragmentedPacket(#[from] crate::client::InvalidFragmentedPacketError),
 
     InvalidSize,
+    InvalidChecksum,
 }
 
 // TODO: this function starts parsing/copying things the user might not need nor want (performance, security)
@@ -168,8 +273,19 @@ pub enum InvalidPacketError {
 // fn ConnectionlessPacket::parse_command(&self) -> (ConnectionlessCommandKind, impl Fn() -> Result<ConnectionlessCommand, InvalidCommandError>)
 // for the sequenced packets that closure probably needs to take some (mutable?) TBD client/server netchan state (challenge, sequence +/ server id, last command)
 // as input to xor unscamble/decode idq3 and checksum ioq3
-/// Parse incoming packet
-pub fn parse_packet(mut payload: impl Buf) -> Result<Packet, InvalidPacketError> {
+/// Parse incoming packet of [`ProtocolVersion::Quake3`]
+pub fn parse_packet(payload: impl Buf) -> Result<Packet, InvalidPacketError> {
+    parse_packet_with(payload, ProtocolVersion::Quake3, Challenge::default())
+}
+
+/// Parse incoming packet of `protocol`
+///
+/// For [`ProtocolVersion::IoQuake3`] the checksum of sequenced packets is verified against `challenge`.
+pub fn parse_packet_with(
+    mut payload: impl Buf,
+    protocol: ProtocolVersion,
+    challenge: Challenge,
+) -> Result<Packet, InvalidPacketError> {
     // the bytes crate would be nicer with fallible try_get_* methods https://github.com/tokio-rs/bytes/issues/254
     if payload.remaining() < core::mem::size_of::<i32>() {
         return Err(InvalidPacketError::InvalidSize);
@@ -189,6 +305,19 @@ pub fn parse_packet(mut payload: impl Buf) -> Result<Packet, InvalidPacketError>
             }
             let qport = QPort::new(payload.get_u16_le())?;
 
+            let checksum_challenge = if protocol.has_checksum() {
+                if payload.remaining() < core::mem::size_of::<i32>() {
+                    return Err(InvalidPacketError::InvalidSize);
+                }
+                let checksum = NetchanChecksum(payload.get_i32_le());
+                if checksum != NetchanChecksum::new(challenge, sequence.number()) {
+                    return Err(InvalidPacketError::InvalidChecksum);
+                }
+                Some(challenge)
+            } else {
+                None
+            };
+
             if sequence.is_fragmented() {
                 if payload.remaining() < core::mem::size_of::<u16>() {
                     return Err(InvalidPacketError::InvalidSize);
@@ -201,8 +330,9 @@ pub fn parse_packet(mut payload: impl Buf) -> Result<Packet, InvalidPacketError>
                 let fragment_length = FragmentLength::new(payload.get_u16_le())?;
 
                 let fragment_info = FragmentInfo::new(fragment_start, fragment_length);
-                // TODO: this should be an error, not a panic
-                assert_eq!(usize::from(fragment_info.length()), payload.remaining());
+                if usize::from(fragment_info.length()) != payload.remaining() {
+                    return Err(InvalidPacketError::InvalidSize);
+                }
 
                 let payload = payload.copy_to_bytes(payload.remaining());
                 let packet = crate::client::FragmentedPacket::new(
@@ -210,12 +340,14 @@ pub fn parse_packet(mut payload: impl Buf) -> Result<Packet, InvalidPacketError>
                     qport,
                     fragment_info.start(),
                     payload,
-                )?;
+                )?
+                .with_checksum_opt(checksum_challenge);
                 Packet::Fragmented(packet)
             } else {
                 let payload = payload.copy_to_bytes(payload.remaining());
                 let packet =
-                    crate::client::SequencedPacket::new(sequence.number(), qport, payload)?;
+                    crate::client::SequencedPacket::new(sequence.number(), qport, payload)?
+                        .with_checksum_opt(checksum_challenge);
                 Packet::Sequenced(packet)
             }
         }
@@ -225,12 +357,18 @@ pub fn parse_packet(mut payload: impl Buf) -> Result<Packet, InvalidPacketError>
 }
 
 /// Kind of incoming [`ConnectionlessMessage`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum ConnectionlessCommand {
     GetStatus,
     GetInfo,
     GetChallenge,
     Connect,
+    Disconnect,
     IpAuthorize,
+    Rcon,
+    Heartbeat,
+    GetServers,
+    GetServersExt,
 }
 
 /// `getstatus` [`ConnectionlessCommand`]
@@ -241,8 +379,12 @@ pub const GETINFO_COMMAND: &[u8] = b"getinfo";
 pub const GETCHALLENGE_COMMAND: &[u8] = b"getchallenge";
 /// `connect` [`ConnectionlessCommand`]
 pub const CONNECT_COMMAND: &[u8] = b"connect";
+/// `disconnect` [`ConnectionlessCommand`]
+pub const DISCONNECT_COMMAND: &[u8] = b"disconnect";
 /// `ipAuthorize` [`ConnectionlessCommand`]
 pub const IPAUTHORIZE_COMMAND: &[u8] = b"ipAuthorize";
+/// `rcon` [`ConnectionlessCommand`]
+pub const RCON_COMMAND: &[u8] = b"rcon";
 
 /// Parse error for [`ConnectionlessCommand`]
 #[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
@@ -250,245 +392,1711 @@ pub const IPAUTHORIZE_COMMAND: &[u8] = b"ipAuthorize";
 pub struct ParseCommandError(());
 
 impl ConnectionlessCommand {
+    const COMMANDS: [(&'static [u8], Self); 10] = [
+        (GETSTATUS_COMMAND, Self::GetStatus),
+        (GETINFO_COMMAND, Self::GetInfo),
+        (GETCHALLENGE_COMMAND, Self::GetChallenge),
+        (CONNECT_COMMAND, Self::Connect),
+        (DISCONNECT_COMMAND, Self::Disconnect),
+        (IPAUTHORIZE_COMMAND, Self::IpAuthorize),
+        (RCON_COMMAND, Self::Rcon),
+        (HEARTBEAT_COMMAND, Self::Heartbeat),
+        (GETSERVERS_COMMAND, Self::GetServers),
+        (GETSERVERSEXT_COMMAND, Self::GetServersExt),
+    ];
+
+    /// Case insensitive like `SV_ConnectionlessPacket`
     pub fn parse(bytes: &[u8]) -> Result<Self, ParseCommandError> {
-        // this is case insensitive in SV_ConnectionlessPacket
-        match bytes {
-            GETSTATUS_COMMAND => Ok(Self::GetStatus),
-            GETINFO_COMMAND => Ok(Self::GetInfo),
-            GETCHALLENGE_COMMAND => Ok(Self::GetChallenge),
-            CONNECT_COMMAND => Ok(Self::Connect),
-            IPAUTHORIZE_COMMAND => Ok(Self::IpAuthorize),
+        Self::COMMANDS
+            .iter()
+            .find(|(command, _)| command.eq_ignore_ascii_case(bytes))
+            .map(|&(_, kind)| kind)
+            .ok_or(ParseCommandError(()))
+    }
 
-            _ => Err(ParseCommandError(())),
-        }
+    pub fn as_bytes(&self) -> &'static [u8] {
+        Self::COMMANDS
+            .iter()
+            .find(|(_, kind)| kind == self)
+            .map(|&(command, _)| command)
+            .expect("all commands should be listed")
     }
 }
 
-/// Connectionless incoming `connect` client message
-// TODO: Expose intermediate CompressedConnectMessage for fuzzing and zip-bomb defusal?
-//#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
-pub struct ConnectMessage<KV> {
-    // TODO: UserInfo struct with parsed
-    // - protocol
-    // - challenge
-    // - qport
-    // - rate
-    // - snaps
-    // - cl_anonymous
-    // - cl_voipProtocol
-    // - cl_guid
-    // - password
-    //
-    // and possibly more. Or split into engine/qagame (id/io)?
-    // At least `protocol` will be required for later id/io split parsers and structs
-    // `challenge` and `protocol` are in `challengeResponse` server → client message
-    // `qport` is in sequenced client → server packets
-    //
-    // Also note that the client connect userinfo differs from the calculated server userinfo, e.g. with "ip":"localhost"
-    user_info: InfoMap<KV, KV, { INFO_LIMIT }>,
+/// Connectionless incoming `getchallenge` client message
+///
+/// id Quake 3 sends no arguments, ioq3 adds a client challenge and the game name.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetChallengeMessage {
+    client_challenge: Option<Challenge>,
+    game_name: Option<QString>,
 }
 
-/// Parse error for [`ConnectMessage`]
+/// Error for a [`GetChallengeMessage`] game name that is not a single argument
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidGameNameError(());
+
+/// Parse error for [`GetChallengeMessage`]
 #[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[error("could not parse")]
-pub struct ParseConnectMessageError(());
+pub struct ParseGetChallengeMessageError(());
 
-fn recognize_connect_payload<'s>() -> impl Parser<&'s [u8], &'s [u8], ContextError> {
-    literal(CONNECT_COMMAND)
-}
+impl GetChallengeMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-fn parse_connect_payload(input: &mut &[u8]) -> PResult<ConnectMessage<InfoString>> {
-    // 0. "connect" in recognize_connect_payload()
-    // 1. " " (space)
-    // 2. u16 decoded huffman len, huffman blob
-    // 3. decoded blob: \" .. user_info .. \"
+    /// Challenge of the client, which the server echoes in `challengeResponse`
+    pub fn with_client_challenge(mut self, client_challenge: Challenge) -> Self {
+        self.client_challenge = Some(client_challenge);
+        self
+    }
+
+    /// Game name like `com_gamename`, which the server may check
+    ///
+    /// It is written unquoted, so it must be a single argument without spaces, quotes or comments.
+    pub fn with_game_name(mut self, game_name: QString) -> Result<Self, InvalidGameNameError> {
+        if !cmd::is_argument(game_name.as_bytes()) {
+            return Err(InvalidGameNameError(()));
+        }
+        self.game_name = Some(game_name);
+        Ok(self)
+    }
 
-    // Q3 peeks the "connect", then overwrites the original msg buffer with the huffman decoded part
-    // i.e. it ends up with a complete string buffer of: connect "<user_info>"
-    // could be emulated with https://docs.rs/bytes/latest/bytes/buf/struct.Chain.html but likely not needed
+    pub fn client_challenge(&self) -> Option<Challenge> {
+        self.client_challenge
+    }
 
-    // MSG_ReadStringLine(), Cmd_TokenizeString() probably overkill for MVP
-    // see https://github.com/robo9k/quake3-file-parsers/blob/main/src/lexer.rs
+    pub fn game_name(&self) -> Option<&QStr> {
+        self.game_name.as_deref()
+    }
 
-    let (len, bytes) = seq!(
-        _: literal(b" "),
-        le_u16,
-        rest,
-    )
-    .parse_next(input)?;
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<GetChallengeMessage, ParseGetChallengeMessageError> {
+        const ERROR: ParseGetChallengeMessageError = ParseGetChallengeMessageError(());
+
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), GETCHALLENGE_COMMAND).ok_or(ERROR)?;
+        let mut message = GetChallengeMessage::new();
+        if let Some(client_challenge) = arguments.next() {
+            let client_challenge = cmd::int_argument
+                .parse(client_challenge)
+                .map_err(|_e| ERROR)?;
+            message = message.with_client_challenge(Challenge::new(client_challenge));
+        }
+        if let Some(game_name) = arguments.next() {
+            let game_name = QString::from_bytes(game_name).map_err(|_e| ERROR)?;
+            message = message.with_game_name(game_name).map_err(|_e| ERROR)?;
+        }
+        Ok(message)
+    }
 
-    let mut huff = quake3_huffman::Huffman::adaptive();
-    let mut decoded = BytesMut::new();
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETCHALLENGE_COMMAND);
+        // the game name is the second argument, so it needs a client challenge before it
+        if self.client_challenge.is_some() || self.game_name.is_some() {
+            cmd::write_int_argument(buf, self.client_challenge.unwrap_or_default().into());
+        }
+        if let Some(game_name) = &self.game_name {
+            buf.put_u8(b' ');
+            buf.put_slice(game_name.as_bytes());
+        }
+    }
 
-    huff.decode(&bytes[..], len.into(), &mut decoded).unwrap();
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
 
-    let user_info =
-        delimited(b"\"", take_until(1.., b'\"'), b"\"").parse_next(&mut &decoded[..])?;
+// SVC_Info() and SVC_Status() ignore longer challenges since the infostring exploit
+const MAX_CHALLENGE_LEN: usize = 128;
 
-    let user_info = InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(user_info).unwrap();
+/// Connectionless incoming `getinfo` client message
+///
+/// The optional challenge is echoed in `infoResponse`, so that replies can not be spoofed.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetInfoMessage {
+    challenge: Option<QString>,
+}
 
-    let connect_message = ConnectMessage::new(user_info);
-    Ok(connect_message)
+/// Parse error for [`GetInfoMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseGetInfoMessageError(());
+
+// optional challenge in `Cmd_Argv(1)`, an empty one is like none
+fn parse_challenge_argument(mut arguments: cmd::Tokens<'_>) -> Result<Option<QString>, ()> {
+    match arguments.next() {
+        Some(challenge) if challenge.len() > MAX_CHALLENGE_LEN => Err(()),
+        Some(challenge) if !challenge.is_empty() => {
+            QString::from_bytes(challenge).map(Some).map_err(|_e| ())
+        }
+        _ => Ok(None),
+    }
 }
 
-impl<KV> ConnectMessage<KV> {
-    pub fn new(user_info: InfoMap<KV, KV, { INFO_LIMIT }>) -> Self {
-        Self { user_info }
+impl GetInfoMessage {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn user_info(&self) -> &InfoMap<KV, KV, { INFO_LIMIT }> {
-        &self.user_info
+    pub fn with_challenge(mut self, challenge: QString) -> Self {
+        self.challenge = Some(challenge);
+        self
+    }
+
+    pub fn challenge(&self) -> Option<&QStr> {
+        self.challenge.as_deref()
     }
 
     pub fn parse_packet(
         packet: &ConnectionlessPacket,
-    ) -> Result<ConnectMessage<InfoString>, ParseConnectMessageError> {
-        let payload = packet.payload();
-        let mut payload = &payload.as_ref();
-        let (connect_message,) = seq!(_: recognize_connect_payload(), parse_connect_payload)
-            .parse(payload)
-            .map_err(|_e| ParseConnectMessageError(()))?;
-        Ok(connect_message)
+    ) -> Result<GetInfoMessage, ParseGetInfoMessageError> {
+        const ERROR: ParseGetInfoMessageError = ParseGetInfoMessageError(());
+
+        let arguments = cmd::command_arguments(packet.payload(), GETINFO_COMMAND).ok_or(ERROR)?;
+        let challenge = parse_challenge_argument(arguments).map_err(|_e| ERROR)?;
+        Ok(GetInfoMessage { challenge })
     }
-}
 
-/// Connectionless incoming [`Packet`]
-pub enum ConnectionlessMessage {
-    GetStatus(()),
-    GetInfo(()),
-    GetChallenge(()),
-    Connect(ConnectMessage<InfoString>), // that <KV> generic is annoying here, maybe less so if this were OwnedConnectionlessMessage ?
-    IpAuthorize(()),
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETINFO_COMMAND);
+        if let Some(challenge) = &self.challenge {
+            buf.put_u8(b' ');
+            buf.put_slice(challenge.as_bytes());
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Connectionless incoming `getstatus` client message
+///
+/// The optional challenge is echoed in `statusResponse`, so that replies can not be spoofed.
+#[derive(Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetStatusMessage {
+    challenge: Option<QString>,
+}
 
-    #[test]
-    fn sequencedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
-        assert!(
-            SequencedPacket::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE]).is_err()
-        );
+/// Parse error for [`GetStatusMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseGetStatusMessageError(());
 
-        assert!(
-            SequencedPacket::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE - 1])
-                .is_ok()
-        );
+impl GetStatusMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        Ok(())
+    pub fn with_challenge(mut self, challenge: QString) -> Self {
+        self.challenge = Some(challenge);
+        self
     }
 
-    #[test]
-    fn fragmentedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
-        assert!(FragmentedPacket::new(
-            PacketSequenceNumber::new(42)?,
-            FragmentStart::new(42)?,
-            vec![0; FRAGMENT_SIZE + 1]
-        )
-        .is_err());
+    pub fn challenge(&self) -> Option<&QStr> {
+        self.challenge.as_deref()
+    }
 
-        assert!(FragmentedPacket::new(
-            PacketSequenceNumber::new(42)?,
-            FragmentStart::new(42)?,
-            vec![0; FRAGMENT_SIZE]
-        )
-        .is_ok());
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<GetStatusMessage, ParseGetStatusMessageError> {
+        const ERROR: ParseGetStatusMessageError = ParseGetStatusMessageError(());
 
-        Ok(())
+        let arguments = cmd::command_arguments(packet.payload(), GETSTATUS_COMMAND).ok_or(ERROR)?;
+        let challenge = parse_challenge_argument(arguments).map_err(|_e| ERROR)?;
+        Ok(GetStatusMessage { challenge })
     }
 
-    #[test]
-    fn parse_packet_invalidsize() {
-        let mut payload = &b"\xFF"[..];
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETSTATUS_COMMAND);
+        if let Some(challenge) = &self.challenge {
+            buf.put_u8(b' ');
+            buf.put_slice(challenge.as_bytes());
+        }
+    }
 
-        let packet = parse_packet(&mut payload);
-        assert!(matches!(packet, Err(InvalidPacketError::InvalidSize)));
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
     }
+}
 
-    #[test]
-    fn parse_packet_connectionless() -> Result<(), Box<dyn std::error::Error>> {
-        let mut payload = &b"\xFF\xFF\xFF\xFF\xDE\xAD\xBE\xEF"[..];
+/// `getservers` master server command
+pub const GETSERVERS_COMMAND: &[u8] = b"getservers";
 
-        let packet = parse_packet(&mut payload)?;
-        match packet {
-            Packet::Connectionless(packet) => {
-                assert_eq!(packet.payload(), &b"\xDE\xAD\xBE\xEF"[..]);
-            }
-            _ => panic!(),
+const EMPTY_KEYWORD: &[u8] = b"empty";
+const FULL_KEYWORD: &[u8] = b"full";
+
+/// Connectionless incoming `getservers` master server message
+///
+/// By default the master server only lists game servers which are neither empty nor full.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetServersMessage {
+    protocol: c_int,
+    empty: bool,
+    full: bool,
+}
+
+/// Parse error for [`GetServersMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseGetServersMessageError(());
+
+impl GetServersMessage {
+    pub fn new(protocol: c_int) -> Self {
+        Self {
+            protocol,
+            empty: false,
+            full: false,
         }
+    }
 
-        Ok(())
+    /// Also list empty game servers
+    pub fn with_empty(mut self) -> Self {
+        self.empty = true;
+        self
     }
 
-    #[test]
-    fn parse_packet_sequenced() -> Result<(), Box<dyn std::error::Error>> {
-        let mut payload = &b"\x00\x00\x00\x00\x9A\x02\xDE\xAD\xBE\xEF"[..];
+    /// Also list full game servers
+    pub fn with_full(mut self) -> Self {
+        self.full = true;
+        self
+    }
 
-        let packet = parse_packet(&mut payload)?;
-        match packet {
-            Packet::Sequenced(packet) => {
-                assert_eq!(packet.sequence(), PacketSequenceNumber::new(0)?);
-                assert_eq!(packet.qport(), QPort::new(666)?);
-                assert_eq!(packet.payload(), &b"\xDE\xAD\xBE\xEF"[..]);
-            }
-            _ => panic!(),
+    /// Protocol of the listed game servers, e.g. [`ProtocolVersion::number`]
+    pub fn protocol(&self) -> c_int {
+        self.protocol
+    }
+
+    pub fn empty(&self) -> bool {
+        self.empty
+    }
+
+    pub fn full(&self) -> bool {
+        self.full
+    }
+
+    /// Other keywords like dpmaster's `gametype=` are ignored
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<GetServersMessage, ParseGetServersMessageError> {
+        const ERROR: ParseGetServersMessageError = ParseGetServersMessageError(());
+
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), GETSERVERS_COMMAND).ok_or(ERROR)?;
+        let protocol = arguments
+            .next()
+            .and_then(|protocol| cmd::int_argument.parse(protocol).ok())
+            .ok_or(ERROR)?;
+        let keywords: Vec<_> = arguments.collect();
+
+        let mut message = GetServersMessage::new(protocol);
+        message.empty = keywords.contains(&EMPTY_KEYWORD);
+        message.full = keywords.contains(&FULL_KEYWORD);
+        Ok(message)
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETSERVERS_COMMAND);
+        cmd::write_int_argument(buf, self.protocol);
+        if self.empty {
+            buf.put_u8(b' ');
+            buf.put_slice(EMPTY_KEYWORD);
+        }
+        if self.full {
+            buf.put_u8(b' ');
+            buf.put_slice(FULL_KEYWORD);
         }
+    }
 
-        Ok(())
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
     }
+}
 
-    #[test]
-    fn parse_packet_fragmented() -> Result<(), Box<dyn std::error::Error>> {
-        let mut payload = &b"\x00\x00\x00\x80\x9A\x02\x01\x00\x04\x00\xDE\xAD\xBE\xEF"[..];
+/// Connectionless `disconnect` message, sent by both game clients and game servers
+///
+/// `SV_ConnectionlessPacket` ignores this, it is a leftover of a game client that was connected to a local game server before.
+/// `CL_DisconnectPacket` drops the connection if this comes from the connected game server.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct DisconnectMessage(());
 
-        let packet = parse_packet(&mut payload)?;
-        match packet {
-            Packet::Fragmented(packet) => {
-                assert_eq!(packet.sequence(), PacketSequenceNumber::new(0)?);
-                assert_eq!(packet.qport(), QPort::new(666)?);
-                assert_eq!(
-                    packet.fragment_info(),
-                    FragmentInfo::new(FragmentStart::new(1)?, FragmentLength::new(4)?)
-                );
-                assert_eq!(packet.payload(), &b"\xDE\xAD\xBE\xEF"[..]);
-            }
-            _ => panic!(),
+/// Parse error for [`DisconnectMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseDisconnectMessageError(());
+
+impl DisconnectMessage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Arguments are ignored like in the engine
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<DisconnectMessage, ParseDisconnectMessageError> {
+        cmd::command_arguments(packet.payload(), DISCONNECT_COMMAND)
+            .ok_or(ParseDisconnectMessageError(()))?;
+        Ok(DisconnectMessage::new())
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(DISCONNECT_COMMAND);
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// Decision of the auth server in [`IpAuthorizeMessage`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum IpAuthorization {
+    /// The CD key is valid
+    Accept,
+    /// The game client may only play the demo
+    Demo,
+    /// The CD key is not known (yet)
+    Unknown,
+    /// The CD key is invalid or already in use, which is any other keyword in `SV_AuthorizeIpPacket`
+    Deny,
+}
+
+impl IpAuthorization {
+    fn parse(bytes: &[u8]) -> Self {
+        if bytes.eq_ignore_ascii_case(b"accept") {
+            Self::Accept
+        } else if bytes.eq_ignore_ascii_case(b"demo") {
+            Self::Demo
+        } else if bytes.eq_ignore_ascii_case(b"unknown") {
+            Self::Unknown
+        } else {
+            Self::Deny
         }
+    }
 
-        Ok(())
+    fn as_bytes(&self) -> &'static [u8] {
+        match self {
+            Self::Accept => b"accept",
+            Self::Demo => b"demo",
+            Self::Unknown => b"unknown",
+            Self::Deny => b"deny",
+        }
     }
+}
 
-    #[test]
-    fn connectmessage_parse_message() -> Result<(), Box<dyn std::error::Error>> {
-        const encoded_bytes: [u8; 239] = hex_literal::hex!(
-            "
-            63 6F 6E 6E 65 63 74 20
-            28 01
-            44 74 30 8e 05 0c c7 26 
-            c3 14 ec 8e f9 67 d0 1a 4e 29 98 01 c7 c3 7a 30 
-            2c 2c 19 1c 13 87 c2 de 71 0a 5c ac 30 cd 40 ce 
-            3a ca af 96 2a b0 d9 3a b7 b0 fd 4d a8 0e c9 ba 
-            79 4c 28 0a c4 0a 4f 83 02 9b 9f 69 e4 0a c3 38 
-            47 9b cf 22 af 61 f6 64 6f 13 7c a3 ae 1f af 06 
-            52 b7 3c a3 06 5f 3a f4 8f 66 d2 40 ac ee 2b 2d 
-            ea 38 18 f9 b7 f2 36 37 80 ea 17 e9 d5 40 58 f7 
-            0f c6 b2 3a 85 e5 bb ca f7 78 77 09 2c e1 e5 7b 
-            cc ad 59 0f 3c ea 67 2a 37 1a 31 c7 83 e5 02 d7 
-            d1 dd c0 73 eb e6 5d 4c 32 87 a4 a4 8d 2e 1b 08 
-            0b 38 11 ac 7b 9a 34 16 e2 e6 d1 3b f0 f8 f2 99 
-            da c4 91 b7 4b 53 cf 82 a6 da 10 61 89 b0 5b 6c 
-            6e c3 46 e3 b7 7c 19 62 38 ac 42 48 23 ab 11 e6 
-            20 0a b8 75 91 26 12 6e 92 25 65 c9 00       
-        "
-        );
+/// Error for [`IpAuthorizeMessage`] reason that can not be quoted
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidIpAuthorizeReasonError(());
 
-        let packet = ConnectionlessPacket::new(&encoded_bytes[..])?;
-        let connect_message = ConnectMessage::<InfoString>::parse_packet(&packet)?;
+/// Connectionless incoming `ipAuthorize` auth server message
+///
+/// This is the reply to `getIpAuthorize` for a connecting game client with `challenge`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct IpAuthorizeMessage {
+    challenge: Challenge,
+    authorization: IpAuthorization,
+    reason: Option<QString>,
+}
 
-        let user_info = connect_message.user_info();
+/// Parse error for [`IpAuthorizeMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseIpAuthorizeMessageError(());
+
+impl IpAuthorizeMessage {
+    pub fn new(challenge: Challenge, authorization: IpAuthorization) -> Self {
+        Self {
+            challenge,
+            authorization,
+            reason: None,
+        }
+    }
 
-        assert!(user_info.len() == 19);
+    /// Reason which the game server prints to the game client, it must not contain `"`
+    pub fn with_reason(mut self, reason: QString) -> Result<Self, InvalidIpAuthorizeReasonError> {
+        if reason.as_bytes().contains(&b'"') {
+            return Err(InvalidIpAuthorizeReasonError(()));
+        }
+        self.reason = Some(reason);
+        Ok(self)
+    }
+
+    pub fn challenge(&self) -> Challenge {
+        self.challenge
+    }
+
+    pub fn authorization(&self) -> IpAuthorization {
+        self.authorization
+    }
+
+    pub fn reason(&self) -> Option<&QStr> {
+        self.reason.as_deref()
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<IpAuthorizeMessage, ParseIpAuthorizeMessageError> {
+        const ERROR: ParseIpAuthorizeMessageError = ParseIpAuthorizeMessageError(());
+
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), IPAUTHORIZE_COMMAND).ok_or(ERROR)?;
+        let challenge = arguments
+            .next()
+            .and_then(|challenge| cmd::int_argument.parse(challenge).ok())
+            .ok_or(ERROR)?;
+        let authorization = IpAuthorization::parse(arguments.next().ok_or(ERROR)?);
+        let reason = arguments
+            .next()
+            .filter(|reason| !reason.is_empty())
+            .map(|reason| QString::from_bytes(reason).map_err(|_e| ERROR))
+            .transpose()?;
+
+        Ok(IpAuthorizeMessage {
+            challenge: Challenge::new(challenge),
+            authorization,
+            reason,
+        })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(IPAUTHORIZE_COMMAND);
+        cmd::write_int_argument(buf, self.challenge.into());
+        buf.put_u8(b' ');
+        buf.put_slice(self.authorization.as_bytes());
+        if let Some(reason) = &self.reason {
+            buf.put_slice(b" \"");
+            buf.put_slice(reason.as_bytes());
+            buf.put_u8(b'"');
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// Error for an [`RconMessage`] password that is not a single argument
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidRconPasswordError(());
+
+/// Connectionless incoming `rcon` client message
+///
+/// The game server runs the command line if the password matches `rconPassword`,
+/// and replies with its output in [`crate::client::PrintMessage`].
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct RconMessage {
+    password: QString,
+    command: QString,
+}
+
+/// Parse error for [`RconMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseRconMessageError(());
+
+// SVC_RemoteCommand() compares the first argument of Cmd_TokenizeString(),
+// but skips the password in the raw command line until the next space
+fn is_rcon_password(password: &[u8]) -> bool {
+    cmd::is_argument(password)
+}
+
+impl RconMessage {
+    pub fn new(password: QString, command: QString) -> Result<Self, InvalidRconPasswordError> {
+        if is_rcon_password(password.as_bytes()) {
+            Ok(Self { password, command })
+        } else {
+            Err(InvalidRconPasswordError(()))
+        }
+    }
+
+    pub fn password(&self) -> &QStr {
+        &self.password
+    }
+
+    /// Command line as typed into the game server console, it may contain several quoted arguments
+    pub fn command(&self) -> &QStr {
+        &self.command
+    }
+
+    /// Arguments of [`Self::command`] like `Cmd_TokenizeString`
+    pub fn arguments(&self) -> Vec<&[u8]> {
+        cmd::tokenize(self.command.as_bytes())
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<RconMessage, ParseRconMessageError> {
+        const ERROR: ParseRconMessageError = ParseRconMessageError(());
+
+        let mut arguments = cmd::command_arguments(packet.payload(), RCON_COMMAND).ok_or(ERROR)?;
+        let password = arguments
+            .next()
+            .filter(|password| is_rcon_password(password))
+            .ok_or(ERROR)?;
+        let command = arguments.rest();
+        let command = &command[command.iter().take_while(|&&c| c == b' ').count()..];
+
+        let password = QString::from_bytes(password).map_err(|_e| ERROR)?;
+        let command = QString::from_bytes(command).map_err(|_e| ERROR)?;
+        Ok(RconMessage { password, command })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(RCON_COMMAND);
+        buf.put_u8(b' ');
+        buf.put_slice(self.password.as_bytes());
+        buf.put_u8(b' ');
+        buf.put_slice(self.command.as_bytes());
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `heartbeat` master server command
+pub const HEARTBEAT_COMMAND: &[u8] = b"heartbeat";
+
+/// Heartbeat tag of id Quake 3, like `HEARTBEAT_GAME`
+pub const QUAKE3_HEARTBEAT_TAG: &[u8] = b"QuakeArena-1";
+/// Heartbeat tag of ioq3 and dpmaster, like `HEARTBEAT_FOR_MASTER`
+///
+/// ioq3 also sends this as the flatline when the game server shuts down.
+pub const DARKPLACES_HEARTBEAT_TAG: &[u8] = b"DarkPlaces";
+
+/// Connectionless incoming `heartbeat` master server message
+///
+/// A game server sends this to announce itself, the master server then queries it with `getinfo`.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct HeartbeatMessage {
+    tag: QString,
+}
+
+/// Parse error for [`HeartbeatMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseHeartbeatMessageError(());
+
+impl HeartbeatMessage {
+    /// Tag like [`QUAKE3_HEARTBEAT_TAG`] or [`DARKPLACES_HEARTBEAT_TAG`]
+    pub fn new(tag: QString) -> Self {
+        Self { tag }
+    }
+
+    pub fn tag(&self) -> &QStr {
+        &self.tag
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<HeartbeatMessage, ParseHeartbeatMessageError> {
+        const ERROR: ParseHeartbeatMessageError = ParseHeartbeatMessageError(());
+
+        // SV_MasterHeartbeat() ends with a newline, which ends the command line
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), HEARTBEAT_COMMAND).ok_or(ERROR)?;
+        let tag = arguments.next().ok_or(ERROR)?;
+
+        let tag = QString::from_bytes(tag).map_err(|_e| ERROR)?;
+        Ok(HeartbeatMessage { tag })
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(HEARTBEAT_COMMAND);
+        buf.put_u8(b' ');
+        buf.put_slice(self.tag.as_bytes());
+        buf.put_u8(b'\n');
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// `getserversExt` master server command
+pub const GETSERVERSEXT_COMMAND: &[u8] = b"getserversExt";
+
+const IPV4_KEYWORD: &[u8] = b"ipv4";
+const IPV6_KEYWORD: &[u8] = b"ipv6";
+
+/// Connectionless incoming `getserversExt` master server message
+///
+/// This is the dpmaster extension of [`GetServersMessage`] which also lists IPv6 game servers.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct GetServersExtMessage {
+    game_name: QString,
+    protocol: c_int,
+    ipv4: bool,
+    ipv6: bool,
+    empty: bool,
+    full: bool,
+}
+
+/// Parse error for [`GetServersExtMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseGetServersExtMessageError(());
+
+impl GetServersExtMessage {
+    /// Game name like `com_gamename`, which the game server sends in its heartbeat and info
+    pub fn new(game_name: QString, protocol: c_int) -> Self {
+        Self {
+            game_name,
+            protocol,
+            ipv4: false,
+            ipv6: false,
+            empty: false,
+            full: false,
+        }
+    }
+
+    /// List IPv4 game servers
+    pub fn with_ipv4(mut self) -> Self {
+        self.ipv4 = true;
+        self
+    }
+
+    /// List IPv6 game servers
+    pub fn with_ipv6(mut self) -> Self {
+        self.ipv6 = true;
+        self
+    }
+
+    /// Also list empty game servers
+    pub fn with_empty(mut self) -> Self {
+        self.empty = true;
+        self
+    }
+
+    /// Also list full game servers
+    pub fn with_full(mut self) -> Self {
+        self.full = true;
+        self
+    }
+
+    pub fn game_name(&self) -> &QStr {
+        &self.game_name
+    }
+
+    pub fn protocol(&self) -> c_int {
+        self.protocol
+    }
+
+    pub fn ipv4(&self) -> bool {
+        self.ipv4
+    }
+
+    pub fn ipv6(&self) -> bool {
+        self.ipv6
+    }
+
+    pub fn empty(&self) -> bool {
+        self.empty
+    }
+
+    pub fn full(&self) -> bool {
+        self.full
+    }
+
+    /// Other keywords like dpmaster's `gametype=` are ignored
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<GetServersExtMessage, ParseGetServersExtMessageError> {
+        const ERROR: ParseGetServersExtMessageError = ParseGetServersExtMessageError(());
+
+        let mut arguments =
+            cmd::command_arguments(packet.payload(), GETSERVERSEXT_COMMAND).ok_or(ERROR)?;
+        let game_name = arguments.next().ok_or(ERROR)?;
+        let protocol = arguments
+            .next()
+            .and_then(|protocol| cmd::int_argument.parse(protocol).ok())
+            .ok_or(ERROR)?;
+        let keywords: Vec<_> = arguments.collect();
+
+        let game_name = QString::from_bytes(game_name).map_err(|_e| ERROR)?;
+        let mut message = GetServersExtMessage::new(game_name, protocol);
+        message.ipv4 = keywords.contains(&IPV4_KEYWORD);
+        message.ipv6 = keywords.contains(&IPV6_KEYWORD);
+        message.empty = keywords.contains(&EMPTY_KEYWORD);
+        message.full = keywords.contains(&FULL_KEYWORD);
+        Ok(message)
+    }
+
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        buf.put_slice(GETSERVERSEXT_COMMAND);
+        buf.put_u8(b' ');
+        buf.put_slice(self.game_name.as_bytes());
+        cmd::write_int_argument(buf, self.protocol);
+        for (enabled, keyword) in [
+            (self.ipv4, IPV4_KEYWORD),
+            (self.ipv6, IPV6_KEYWORD),
+            (self.empty, EMPTY_KEYWORD),
+            (self.full, FULL_KEYWORD),
+        ] {
+            if enabled {
+                buf.put_u8(b' ');
+                buf.put_slice(keyword);
+            }
+        }
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// Connectionless incoming `connect` client message
+// TODO: Expose intermediate CompressedConnectMessage for fuzzing and zip-bomb defusal?
+//#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct ConnectMessage<KV> {
+    // see crate::userinfo::UserInfo for typed access
+    user_info: InfoMap<KV, KV, { INFO_LIMIT }>,
+}
+
+/// Error for [`ConnectMessage`] user info that can not be quoted
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("is invalid")]
+pub struct InvalidConnectMessageError(());
+
+/// Parse error for [`ConnectMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[error("could not parse")]
+pub struct ParseConnectMessageError(());
+
+// SV_ConnectionlessPacket() only decompresses a case sensitive "connect" at the very start,
+// then overwrites the original msg buffer with the huffman decoded part
+// i.e. it ends up with a complete string buffer of: connect "<user_info>"
+fn decompress_connect_payload(payload: &[u8]) -> Option<BytesMut> {
+    let compressed = payload.get(CONNECT_COMMAND.len()..)?;
+    // 1. " " (space)
+    // 2. u16 decoded huffman len, huffman blob
+    let (len, bytes) = seq!(
+        _: take(1usize),
+        le_u16,
+        rest::<_, ContextError>,
+    )
+    .parse(compressed)
+    .ok()?;
+
+    let mut huff = quake3_huffman::Huffman::adaptive();
+    let mut decoded = BytesMut::from(&payload[..CONNECT_COMMAND.len() + 1]);
+    huff.decode(bytes, len.into(), &mut decoded).ok()?;
+    Some(decoded)
+}
+
+impl<KV> ConnectMessage<KV> {
+    pub fn user_info(&self) -> &InfoMap<KV, KV, { INFO_LIMIT }> {
+        &self.user_info
+    }
+
+    /// Take the user info, e.g. for [`crate::userinfo::UserInfo`]
+    pub fn into_user_info(self) -> InfoMap<KV, KV, { INFO_LIMIT }> {
+        self.user_info
+    }
+
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<ConnectMessage<InfoString>, ParseConnectMessageError> {
+        const ERROR: ParseConnectMessageError = ParseConnectMessageError(());
+
+        let payload: &[u8] = packet.payload();
+        let decoded;
+        let line = if payload.starts_with(CONNECT_COMMAND) {
+            decoded = decompress_connect_payload(payload).ok_or(ERROR)?;
+            &decoded[..]
+        } else {
+            payload
+        };
+
+        let mut arguments = cmd::command_arguments(line, CONNECT_COMMAND).ok_or(ERROR)?;
+        let user_info = InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(
+            arguments.next().unwrap_or_default(),
+        )
+        .map_err(|_e| ERROR)?;
+        ConnectMessage::new(user_info).map_err(|_e| ERROR)
+    }
+}
+
+impl<KV: InfoKv + AsRef<[u8]>> ConnectMessage<KV> {
+    /// The user info is written as one quoted argument, so it must not contain `"`
+    pub fn new(
+        user_info: InfoMap<KV, KV, { INFO_LIMIT }>,
+    ) -> Result<Self, InvalidConnectMessageError> {
+        if user_info
+            .iter()
+            .any(|(k, v)| k.as_ref().contains(&b'"') || v.as_ref().contains(&b'"'))
+        {
+            Err(InvalidConnectMessageError(()))
+        } else {
+            Ok(Self { user_info })
+        }
+    }
+
+    /// Write like `CL_CheckForResend`, with the quoted userinfo compressed like `Huff_Compress`
+    pub fn write_to(&self, buf: &mut impl BufMut) {
+        let mut user_info = BytesMut::new();
+        user_info.put_u8(b'"');
+        self.user_info.write_to(&mut user_info);
+        user_info.put_u8(b'"');
+
+        let mut huff = quake3_huffman::Huffman::adaptive();
+        let bits = huff.encode(&user_info);
+        // Huff_Compress always counts the byte after the last bit
+        let len = bits.len() / 8 + 1;
+        let mut encoded = bits.into_vec();
+        encoded.resize(len, 0);
+
+        buf.put_slice(CONNECT_COMMAND);
+        buf.put_u8(b' ');
+        buf.put_u16_le(
+            user_info
+                .len()
+                .try_into()
+                .expect("user info should be within INFO_LIMIT"),
+        );
+        buf.put_slice(&encoded);
+    }
+
+    pub fn to_packet(&self) -> Result<ConnectionlessPacket, InvalidConnectionlessPacketError> {
+        let mut buf = BytesMut::new();
+        self.write_to(&mut buf);
+        ConnectionlessPacket::new(buf)
+    }
+}
+
+/// Connectionless incoming [`Packet`]
+pub enum ConnectionlessMessage {
+    GetStatus(GetStatusMessage),
+    GetInfo(GetInfoMessage),
+    GetChallenge(GetChallengeMessage),
+    Connect(ConnectMessage<InfoString>), // that <KV> generic is annoying here, maybe less so if this were OwnedConnectionlessMessage ?
+    Disconnect(DisconnectMessage),
+    IpAuthorize(IpAuthorizeMessage),
+    Rcon(RconMessage),
+    Heartbeat(HeartbeatMessage),
+    GetServers(GetServersMessage),
+    GetServersExt(GetServersExtMessage),
+}
+
+/// Parse error for [`ConnectionlessMessage`]
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ParseConnectionlessMessageError {
+    #[error("unknown command")]
+    UnknownCommand(#[from] UnknownCommandError),
+    #[error("invalid getstatus message")]
+    GetStatus(#[from] ParseGetStatusMessageError),
+    #[error("invalid getinfo message")]
+    GetInfo(#[from] ParseGetInfoMessageError),
+    #[error("invalid getchallenge message")]
+    GetChallenge(#[from] ParseGetChallengeMessageError),
+    #[error("invalid connect message")]
+    Connect(#[from] ParseConnectMessageError),
+    #[error("invalid disconnect message")]
+    Disconnect(#[from] ParseDisconnectMessageError),
+    #[error("invalid ipAuthorize message")]
+    IpAuthorize(#[from] ParseIpAuthorizeMessageError),
+    #[error("invalid rcon message")]
+    Rcon(#[from] ParseRconMessageError),
+    #[error("invalid heartbeat message")]
+    Heartbeat(#[from] ParseHeartbeatMessageError),
+    #[error("invalid getservers message")]
+    GetServers(#[from] ParseGetServersMessageError),
+    #[error("invalid getserversExt message")]
+    GetServersExt(#[from] ParseGetServersExtMessageError),
+}
+
+impl ConnectionlessMessage {
+    pub fn command(&self) -> ConnectionlessCommand {
+        match self {
+            Self::GetStatus(_) => ConnectionlessCommand::GetStatus,
+            Self::GetInfo(_) => ConnectionlessCommand::GetInfo,
+            Self::GetChallenge(_) => ConnectionlessCommand::GetChallenge,
+            Self::Connect(_) => ConnectionlessCommand::Connect,
+            Self::Disconnect(_) => ConnectionlessCommand::Disconnect,
+            Self::IpAuthorize(_) => ConnectionlessCommand::IpAuthorize,
+            Self::Rcon(_) => ConnectionlessCommand::Rcon,
+            Self::Heartbeat(_) => ConnectionlessCommand::Heartbeat,
+            Self::GetServers(_) => ConnectionlessCommand::GetServers,
+            Self::GetServersExt(_) => ConnectionlessCommand::GetServersExt,
+        }
+    }
+
+    /// Parse the message by its first argument like `SV_ConnectionlessPacket`
+    pub fn parse_packet(
+        packet: &ConnectionlessPacket,
+    ) -> Result<ConnectionlessMessage, ParseConnectionlessMessageError> {
+        let command = cmd::Tokens::new(packet.payload())
+            .next()
+            .unwrap_or_default();
+        let command = ConnectionlessCommand::parse(command)
+            .map_err(|_e| UnknownCommandError::new(command))?;
+        let message = match command {
+            ConnectionlessCommand::GetStatus => {
+                Self::GetStatus(GetStatusMessage::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::GetInfo => Self::GetInfo(GetInfoMessage::parse_packet(packet)?),
+            ConnectionlessCommand::GetChallenge => {
+                Self::GetChallenge(GetChallengeMessage::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::Connect => {
+                Self::Connect(ConnectMessage::<InfoString>::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::Disconnect => {
+                Self::Disconnect(DisconnectMessage::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::IpAuthorize => {
+                Self::IpAuthorize(IpAuthorizeMessage::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::Rcon => Self::Rcon(RconMessage::parse_packet(packet)?),
+            ConnectionlessCommand::Heartbeat => {
+                Self::Heartbeat(HeartbeatMessage::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::GetServers => {
+                Self::GetServers(GetServersMessage::parse_packet(packet)?)
+            }
+            ConnectionlessCommand::GetServersExt => {
+                Self::GetServersExt(GetServersExtMessage::parse_packet(packet)?)
+            }
+        };
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quake3::info::InfoStr;
+
+    #[test]
+    fn sequencedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(
+            SequencedPacket::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE]).is_err()
+        );
+
+        assert!(
+            SequencedPacket::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE - 1])
+                .is_ok()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequencedpacket_transmit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut netchan = Netchan::new(QPort::new(27960)?);
+
+        let packet = SequencedPacket::transmit(&mut netchan, vec![0; 42])?;
+        assert_eq!(packet.sequence(), PacketSequenceNumber::new(1)?);
+        assert_eq!(netchan.outgoing_sequence(), PacketSequenceNumber::new(2)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentedpacket_new() -> Result<(), Box<dyn std::error::Error>> {
+        assert!(FragmentedPacket::new(
+            PacketSequenceNumber::new(42)?,
+            FragmentStart::new(42)?,
+            vec![0; FRAGMENT_SIZE + 1]
+        )
+        .is_err());
+
+        assert!(FragmentedPacket::new(
+            PacketSequenceNumber::new(42)?,
+            FragmentStart::new(42)?,
+            vec![0; FRAGMENT_SIZE]
+        )
+        .is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentedpacket_next_from() -> Result<(), Box<dyn std::error::Error>> {
+        let mut queue = FragmentQueue::new(PacketSequenceNumber::new(42)?, vec![0; FRAGMENT_SIZE])?;
+
+        let packet = FragmentedPacket::next_from(&mut queue).expect("first fragment");
+        assert_eq!(packet.sequence(), PacketSequenceNumber::new(42)?);
+        assert!(!packet.is_last());
+
+        let packet = FragmentedPacket::next_from(&mut queue).expect("terminating fragment");
+        assert_eq!(
+            packet.fragment_info(),
+            FragmentInfo::new(
+                FragmentStart::new(FRAGMENT_SIZE as u16)?,
+                FragmentLength::new(0)?
+            )
+        );
+        assert!(packet.is_last());
+
+        assert!(FragmentedPacket::next_from(&mut queue).is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn sequencedpacket_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = SequencedPacket::new(PacketSequenceNumber::new(0)?, &b"\xDE\xAD\xBE\xEF"[..])?;
+
+        let bytes = packet.to_bytes();
+        assert_eq!(bytes, &b"\x00\x00\x00\x00\xDE\xAD\xBE\xEF"[..]);
+
+        let parsed = crate::client::parse_server_packet(bytes)?;
+        assert_eq!(parsed, crate::client::ServerPacket::Sequenced(packet));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentedpacket_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = FragmentedPacket::new(
+            PacketSequenceNumber::new(0)?,
+            FragmentStart::new(1)?,
+            &b"\xDE\xAD\xBE\xEF"[..],
+        )?;
+
+        let bytes = packet.to_bytes();
+        assert_eq!(
+            bytes,
+            &b"\x00\x00\x00\x80\x01\x00\x04\x00\xDE\xAD\xBE\xEF"[..]
+        );
+
+        let parsed = crate::client::parse_server_packet(bytes)?;
+        assert_eq!(parsed, crate::client::ServerPacket::Fragmented(packet));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fragmentedpacket_with_checksum_to_bytes() -> Result<(), Box<dyn std::error::Error>> {
+        let challenge = Challenge::new(0x12345678);
+        let packet = FragmentedPacket::new(
+            PacketSequenceNumber::new(3)?,
+            FragmentStart::new(1)?,
+            &b"\xDE\xAD\xBE\xEF"[..],
+        )?
+        .with_checksum(challenge);
+
+        let bytes = packet.to_bytes();
+        assert_eq!(
+            bytes,
+            &b"\x03\x00\x00\x80\x10\x55\xA9\x24\x01\x00\x04\x00\xDE\xAD\xBE\xEF"[..]
+        );
+
+        let parsed = crate::client::parse_server_packet_with(
+            bytes.clone(),
+            ProtocolVersion::IoQuake3,
+            challenge,
+        )?;
+        assert_eq!(parsed, crate::client::ServerPacket::Fragmented(packet));
+
+        assert!(matches!(
+            crate::client::parse_server_packet_with(
+                bytes,
+                ProtocolVersion::IoQuake3,
+                Challenge::new(0x0BADF00D)
+            ),
+            Err(crate::client::InvalidServerPacketError::InvalidChecksum)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_packet_with_invalidsize() {
+        let mut payload = &b"\x00\x00\x00\x00\x9A\x02\x00"[..];
+
+        let packet = parse_packet_with(
+            &mut payload,
+            ProtocolVersion::IoQuake3,
+            Challenge::default(),
+        );
+        assert!(matches!(packet, Err(InvalidPacketError::InvalidSize)));
+    }
+
+    #[test]
+    fn parse_packet_invalidsize() {
+        let mut payload = &b"\xFF"[..];
+
+        let packet = parse_packet(&mut payload);
+        assert!(matches!(packet, Err(InvalidPacketError::InvalidSize)));
+    }
+
+    #[test]
+    fn parse_packet_fragment_invalidsize() {
+        for mut payload in [
+            &b"\x00\x00\x00\x80\x9A\x02\x01"[..],
+            &b"\x00\x00\x00\x80\x9A\x02\x01\x00\x04"[..],
+            // the fragment length does not match the payload
+            &b"\x00\x00\x00\x80\x9A\x02\x01\x00\x05\x00\xDE\xAD\xBE\xEF"[..],
+            &b"\x00\x00\x00\x80\x9A\x02\x01\x00\x03\x00\xDE\xAD\xBE\xEF"[..],
+        ] {
+            let packet = parse_packet(&mut payload);
+            assert!(matches!(packet, Err(InvalidPacketError::InvalidSize)));
+        }
+    }
+
+    #[test]
+    fn parse_packet_connectionless() -> Result<(), Box<dyn std::error::Error>> {
+        let mut payload = &b"\xFF\xFF\xFF\xFF\xDE\xAD\xBE\xEF"[..];
+
+        let packet = parse_packet(&mut payload)?;
+        match packet {
+            Packet::Connectionless(packet) => {
+                assert_eq!(packet.payload(), &b"\xDE\xAD\xBE\xEF"[..]);
+            }
+            _ => panic!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_packet_sequenced() -> Result<(), Box<dyn std::error::Error>> {
+        let mut payload = &b"\x00\x00\x00\x00\x9A\x02\xDE\xAD\xBE\xEF"[..];
+
+        let packet = parse_packet(&mut payload)?;
+        match packet {
+            Packet::Sequenced(packet) => {
+                assert_eq!(packet.sequence(), PacketSequenceNumber::new(0)?);
+                assert_eq!(packet.qport(), QPort::new(666)?);
+                assert_eq!(packet.payload(), &b"\xDE\xAD\xBE\xEF"[..]);
+            }
+            _ => panic!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_packet_fragmented() -> Result<(), Box<dyn std::error::Error>> {
+        let mut payload = &b"\x00\x00\x00\x80\x9A\x02\x01\x00\x04\x00\xDE\xAD\xBE\xEF"[..];
+
+        let packet = parse_packet(&mut payload)?;
+        match packet {
+            Packet::Fragmented(packet) => {
+                assert_eq!(packet.sequence(), PacketSequenceNumber::new(0)?);
+                assert_eq!(packet.qport(), QPort::new(666)?);
+                assert_eq!(
+                    packet.fragment_info(),
+                    FragmentInfo::new(FragmentStart::new(1)?, FragmentLength::new(4)?)
+                );
+                assert_eq!(packet.payload(), &b"\xDE\xAD\xBE\xEF"[..]);
+            }
+            _ => panic!(),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"getservers 68"[..])?;
+        assert_eq!(
+            GetServersMessage::parse_packet(&packet)?,
+            GetServersMessage::new(68)
+        );
+
+        let packet = ConnectionlessPacket::new(&b"getservers 71 full gametype=4 empty"[..])?;
+        assert_eq!(
+            GetServersMessage::parse_packet(&packet)?,
+            GetServersMessage::new(71).with_empty().with_full()
+        );
+
+        let packet = ConnectionlessPacket::new(&b"getservers empty"[..])?;
+        assert!(GetServersMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversmessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = GetServersMessage::new(68).with_empty().with_full();
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"getservers 68 empty full"[..]);
+        assert_eq!(GetServersMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn disconnectmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = DisconnectMessage::new().to_packet()?;
+        assert_eq!(packet.payload(), &b"disconnect"[..]);
+        assert_eq!(
+            DisconnectMessage::parse_packet(&packet)?,
+            DisconnectMessage::new()
+        );
+
+        let packet = ConnectionlessPacket::new(&b"disconnect now"[..])?;
+        assert!(DisconnectMessage::parse_packet(&packet).is_ok());
+
+        let packet = ConnectionlessPacket::new(&b"disconnected"[..])?;
+        assert!(DisconnectMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ipauthorizemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"ipAuthorize -42 accept KEY_IS_GOOD 0"[..])?;
+        let message = IpAuthorizeMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Challenge::new(-42));
+        assert_eq!(message.authorization(), IpAuthorization::Accept);
+        assert_eq!(message.reason(), Some(QStr::from_bytes(b"KEY_IS_GOOD")?));
+
+        let packet = ConnectionlessPacket::new(&b"ipAuthorize 1 DEMO"[..])?;
+        let message = IpAuthorizeMessage::parse_packet(&packet)?;
+        assert_eq!(message.authorization(), IpAuthorization::Demo);
+        assert_eq!(message.reason(), None);
+
+        let packet = ConnectionlessPacket::new(&b"ipAuthorize 1 banned \"\""[..])?;
+        let message = IpAuthorizeMessage::parse_packet(&packet)?;
+        assert_eq!(message.authorization(), IpAuthorization::Deny);
+        assert_eq!(message.reason(), None);
+
+        let packet = ConnectionlessPacket::new(&b"ipAuthorize x accept"[..])?;
+        assert!(IpAuthorizeMessage::parse_packet(&packet).is_err());
+
+        let packet = ConnectionlessPacket::new(&b"ipAuthorize 1"[..])?;
+        assert!(IpAuthorizeMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn ipauthorizemessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = IpAuthorizeMessage::new(Challenge::new(7), IpAuthorization::Unknown);
+        assert!(message
+            .clone()
+            .with_reason(QString::from_bytes(&b"\"quoted\""[..])?)
+            .is_err());
+
+        let message = message.with_reason(QString::from_bytes(&b"CD key in use"[..])?)?;
+        let packet = message.to_packet()?;
+        assert_eq!(
+            packet.payload(),
+            &b"ipAuthorize 7 unknown \"CD key in use\""[..]
+        );
+        assert_eq!(IpAuthorizeMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectionlesscommand_parse() -> Result<(), Box<dyn std::error::Error>> {
+        assert_eq!(
+            ConnectionlessCommand::parse(b"GetStatus")?,
+            ConnectionlessCommand::GetStatus
+        );
+        assert_eq!(
+            ConnectionlessCommand::parse(b"getserversext")?,
+            ConnectionlessCommand::GetServersExt
+        );
+        assert_eq!(
+            ConnectionlessCommand::IpAuthorize.as_bytes(),
+            b"ipAuthorize"
+        );
+        assert!(ConnectionlessCommand::parse(b"getstatus2").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectionlessmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"GETINFO xyz"[..])?;
+        let message = ConnectionlessMessage::parse_packet(&packet)?;
+        assert_eq!(message.command(), ConnectionlessCommand::GetInfo);
+        let ConnectionlessMessage::GetInfo(message) = message else {
+            panic!("should be getinfo");
+        };
+        assert_eq!(message.challenge(), Some(QStr::from_bytes(b"xyz")?));
+
+        let user_info = InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(b"\\name\\player")?;
+        let packet = ConnectMessage::new(user_info)?.to_packet()?;
+        let message = ConnectionlessMessage::parse_packet(&packet)?;
+        assert_eq!(message.command(), ConnectionlessCommand::Connect);
+
+        let packet = ConnectionlessPacket::new(&b"  Rcon secret \"status\" 1 2"[..])?;
+        let ConnectionlessMessage::Rcon(message) = ConnectionlessMessage::parse_packet(&packet)?
+        else {
+            panic!("should be rcon");
+        };
+        assert_eq!(message.password(), QStr::from_bytes(b"secret")?);
+        assert_eq!(message.command(), QStr::from_bytes(b"\"status\" 1 2")?);
+
+        let packet = ConnectionlessPacket::new(&b"getinfo \"x y\" z"[..])?;
+        let ConnectionlessMessage::GetInfo(message) = ConnectionlessMessage::parse_packet(&packet)?
+        else {
+            panic!("should be getinfo");
+        };
+        assert_eq!(message.challenge(), Some(QStr::from_bytes(b"x y")?));
+
+        let packet = ConnectionlessPacket::new(&b"getservers abc"[..])?;
+        assert!(matches!(
+            ConnectionlessMessage::parse_packet(&packet),
+            Err(ParseConnectionlessMessageError::GetServers(_))
+        ));
+
+        let packet = ConnectionlessPacket::new(&b"\"hello\" world"[..])?;
+        match ConnectionlessMessage::parse_packet(&packet) {
+            Err(ParseConnectionlessMessageError::UnknownCommand(e)) => {
+                assert_eq!(e.command(), b"hello")
+            }
+            _ => panic!("should be unknown"),
+        }
+
+        let packet = ConnectionlessPacket::new(&b""[..])?;
+        match ConnectionlessMessage::parse_packet(&packet) {
+            Err(ParseConnectionlessMessageError::UnknownCommand(e)) => {
+                assert_eq!(e.command(), b"")
+            }
+            _ => panic!("should be unknown"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn rconmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet =
+            ConnectionlessPacket::new(&b"rcon secret  say \"hello world\" // comment"[..])?;
+        let message = RconMessage::parse_packet(&packet)?;
+        assert_eq!(message.password(), QStr::from_bytes(b"secret")?);
+        assert_eq!(
+            message.command(),
+            QStr::from_bytes(b"say \"hello world\" // comment")?
+        );
+        assert_eq!(message.arguments(), [&b"say"[..], b"hello world"]);
+
+        let packet = ConnectionlessPacket::new(&b"rcon secret"[..])?;
+        let message = RconMessage::parse_packet(&packet)?;
+        assert_eq!(message.command(), QStr::from_bytes(b"")?);
+
+        let packet = ConnectionlessPacket::new(&b"rcon \"sec ret\" status"[..])?;
+        assert!(RconMessage::parse_packet(&packet).is_err());
+
+        let packet = ConnectionlessPacket::new(&b"rconsecret status"[..])?;
+        assert!(RconMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn rconmessage_new() -> Result<(), Box<dyn std::error::Error>> {
+        let command = QString::from_bytes(&b"status"[..])?;
+        assert!(RconMessage::new(QString::from_bytes(&b""[..])?, command.clone()).is_err());
+        assert!(RconMessage::new(QString::from_bytes(&b"sec ret"[..])?, command.clone()).is_err());
+        assert!(RconMessage::new(QString::from_bytes(&b"sec\"ret"[..])?, command.clone()).is_err());
+        assert!(RconMessage::new(QString::from_bytes(&b"//secret"[..])?, command.clone()).is_err());
+
+        let message = RconMessage::new(QString::from_bytes(&b"secret"[..])?, command)?;
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"rcon secret status"[..]);
+        assert_eq!(RconMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn heartbeatmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"heartbeat DarkPlaces\n"[..])?;
+        let message = HeartbeatMessage::parse_packet(&packet)?;
+        assert_eq!(message.tag(), QStr::from_bytes(DARKPLACES_HEARTBEAT_TAG)?);
+
+        let packet = ConnectionlessPacket::new(&b"heartbeat QuakeArena-1"[..])?;
+        let message = HeartbeatMessage::parse_packet(&packet)?;
+        assert_eq!(message.tag(), QStr::from_bytes(QUAKE3_HEARTBEAT_TAG)?);
+
+        let packet = ConnectionlessPacket::new(&b"heartbeat\n"[..])?;
+        assert!(HeartbeatMessage::parse_packet(&packet).is_err());
+
+        // like Cmd_Argv(1), further arguments are ignored
+        let packet = ConnectionlessPacket::new(&b"heartbeat QuakeArena-1 extra"[..])?;
+        let message = HeartbeatMessage::parse_packet(&packet)?;
+        assert_eq!(message.tag(), QStr::from_bytes(QUAKE3_HEARTBEAT_TAG)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn heartbeatmessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = HeartbeatMessage::new(QString::from_bytes(DARKPLACES_HEARTBEAT_TAG)?);
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"heartbeat DarkPlaces\n"[..]);
+        assert_eq!(HeartbeatMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversextmessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"getserversExt Quake3Arena 68 ipv6 full"[..])?;
+        let message = GetServersExtMessage::parse_packet(&packet)?;
+        assert_eq!(message.game_name(), QStr::from_bytes(b"Quake3Arena")?);
+        assert_eq!(message.protocol(), 68);
+        assert!(!message.ipv4());
+        assert!(message.ipv6());
+        assert!(!message.empty());
+        assert!(message.full());
+
+        let packet = ConnectionlessPacket::new(&b"getserversExt 68"[..])?;
+        assert!(GetServersExtMessage::parse_packet(&packet).is_err());
+
+        let packet = ConnectionlessPacket::new(&b"getserversExt Quake3Arena 68"[..])?;
+        assert!(GetServersMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getserversextmessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = GetServersExtMessage::new(QString::from_bytes(&b"Quake3Arena"[..])?, 71)
+            .with_ipv4()
+            .with_ipv6()
+            .with_empty();
+        let packet = message.to_packet()?;
+        assert_eq!(
+            packet.payload(),
+            &b"getserversExt Quake3Arena 71 ipv4 ipv6 empty"[..]
+        );
+        assert_eq!(GetServersExtMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn getstatusmessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = GetStatusMessage::new();
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"getstatus"[..]);
+        assert_eq!(GetStatusMessage::parse_packet(&packet)?, message);
+
+        let message = GetStatusMessage::new().with_challenge(QString::from_bytes(&b"xxx"[..])?);
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"getstatus xxx"[..]);
+        assert_eq!(GetStatusMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn getinfomessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"getinfo"[..])?;
+        assert_eq!(
+            GetInfoMessage::parse_packet(&packet)?,
+            GetInfoMessage::new()
+        );
+
+        let packet = ConnectionlessPacket::new(&b"getinfo xxx"[..])?;
+        let message = GetInfoMessage::parse_packet(&packet)?;
+        assert_eq!(message.challenge(), Some(QStr::from_bytes(b"xxx")?));
+
+        let mut payload = b"getinfo ".to_vec();
+        payload.extend([b'x'; MAX_CHALLENGE_LEN + 1]);
+        let packet = ConnectionlessPacket::new(payload)?;
+        assert!(GetInfoMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getinfomessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = GetInfoMessage::new().with_challenge(QString::from_bytes(&b"xxx"[..])?);
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"getinfo xxx"[..]);
+        assert_eq!(GetInfoMessage::parse_packet(&packet)?, message);
+
+        Ok(())
+    }
+
+    #[test]
+    fn getchallengemessage_parse_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let packet = ConnectionlessPacket::new(&b"getchallenge"[..])?;
+        let message = GetChallengeMessage::parse_packet(&packet)?;
+        assert_eq!(message, GetChallengeMessage::new());
+
+        let packet = ConnectionlessPacket::new(&b"getchallenge -42 Quake3Arena"[..])?;
+        let message = GetChallengeMessage::parse_packet(&packet)?;
+        assert_eq!(message.client_challenge(), Some(Challenge::new(-42)));
+        assert_eq!(message.game_name(), Some(QStr::from_bytes(b"Quake3Arena")?));
+
+        let packet = ConnectionlessPacket::new(&b"getchallengefoo"[..])?;
+        assert!(GetChallengeMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn getchallengemessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let message = GetChallengeMessage::new();
+        assert_eq!(message.to_packet()?.payload(), &b"getchallenge"[..]);
+
+        let message = GetChallengeMessage::new()
+            .with_client_challenge(Challenge::new(42))
+            .with_game_name(QString::from_bytes(&b"Quake3Arena"[..])?)?;
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"getchallenge 42 Quake3Arena"[..]);
+        assert_eq!(GetChallengeMessage::parse_packet(&packet)?, message);
+
+        let message =
+            GetChallengeMessage::new().with_game_name(QString::from_bytes(&b"baseq3-1"[..])?)?;
+        let packet = message.to_packet()?;
+        assert_eq!(packet.payload(), &b"getchallenge 0 baseq3-1"[..]);
+        let message = GetChallengeMessage::parse_packet(&packet)?;
+        assert_eq!(message.game_name(), Some(QStr::from_bytes(b"baseq3-1")?));
+
+        for game_name in [&b"foo bar"[..], b"foo\"bar", b"foo//bar", b"foo\nbar", b""] {
+            assert!(GetChallengeMessage::new()
+                .with_game_name(QString::from_bytes(game_name)?)
+                .is_err());
+        }
+
+        let packet = ConnectionlessPacket::new(&b"getchallenge 42 \"foo bar\""[..])?;
+        assert!(GetChallengeMessage::parse_packet(&packet).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectmessage_parse_message() -> Result<(), Box<dyn std::error::Error>> {
+        const ENCODED_BYTES: [u8; 239] = hex_literal::hex!(
+            "
+            63 6F 6E 6E 65 63 74 20
+            28 01
+            44 74 30 8e 05 0c c7 26 
+            c3 14 ec 8e f9 67 d0 1a 4e 29 98 01 c7 c3 7a 30 
+            2c 2c 19 1c 13 87 c2 de 71 0a 5c ac 30 cd 40 ce 
+            3a ca af 96 2a b0 d9 3a b7 b0 fd 4d a8 0e c9 ba 
+            79 4c 28 0a c4 0a 4f 83 02 9b 9f 69 e4 0a c3 38 
+            47 9b cf 22 af 61 f6 64 6f 13 7c a3 ae 1f af 06 
+            52 b7 3c a3 06 5f 3a f4 8f 66 d2 40 ac ee 2b 2d 
+            ea 38 18 f9 b7 f2 36 37 80 ea 17 e9 d5 40 58 f7 
+            0f c6 b2 3a 85 e5 bb ca f7 78 77 09 2c e1 e5 7b 
+            cc ad 59 0f 3c ea 67 2a 37 1a 31 c7 83 e5 02 d7 
+            d1 dd c0 73 eb e6 5d 4c 32 87 a4 a4 8d 2e 1b 08 
+            0b 38 11 ac 7b 9a 34 16 e2 e6 d1 3b f0 f8 f2 99 
+            da c4 91 b7 4b 53 cf 82 a6 da 10 61 89 b0 5b 6c 
+            6e c3 46 e3 b7 7c 19 62 38 ac 42 48 23 ab 11 e6 
+            20 0a b8 75 91 26 12 6e 92 25 65 c9 00       
+        "
+        );
+
+        let packet = ConnectionlessPacket::new(&ENCODED_BYTES[..])?;
+        let connect_message = ConnectMessage::<InfoString>::parse_packet(&packet)?;
+
+        let user_info = connect_message.user_info();
+
+        assert!(user_info.len() == 19);
+
+        assert_eq!(connect_message.to_packet()?, packet);
+
+        let user_info = crate::userinfo::UserInfo::from(connect_message.into_user_info());
+        assert_eq!(user_info.qport()?, Some(QPort::new(2033)?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectmessage_parse_invalid() -> Result<(), Box<dyn std::error::Error>> {
+        let mut user_info = InfoMap::<InfoString, InfoString, INFO_LIMIT>::new();
+        user_info.try_insert(
+            InfoString::from_bytes("name")?,
+            InfoString::from_bytes("Sarge")?,
+        )?;
+        let packet = ConnectMessage::new(user_info)?.to_packet()?;
+        let truncated = packet.payload().slice(..packet.payload().len() - 4);
+        let packet = ConnectionlessPacket::new(truncated)?;
+        assert!(matches!(
+            ConnectionlessMessage::parse_packet(&packet),
+            Err(ParseConnectionlessMessageError::Connect(_))
+        ));
+
+        let malformed = b"\"\\name\"";
+        let mut huff = quake3_huffman::Huffman::adaptive();
+        let mut payload = BytesMut::from(&b"connect "[..]);
+        payload.put_u16_le(malformed.len() as u16);
+        payload.put_slice(huff.encode(malformed).as_raw_slice());
+        let packet = ConnectionlessPacket::new(payload)?;
+        assert!(matches!(
+            ConnectionlessMessage::parse_packet(&packet),
+            Err(ParseConnectionlessMessageError::Connect(_))
+        ));
+
+        let packet = ConnectionlessPacket::new(&b"connect"[..])?;
+        assert!(ConnectMessage::<InfoString>::parse_packet(&packet).is_err());
+
+        // a quote would end the quoted user info early
+        let mut user_info = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::new();
+        user_info.try_insert(
+            InfoStr::from_bytes(b"name")?,
+            InfoStr::from_bytes(b"Sar\"ge")?,
+        )?;
+        assert!(ConnectMessage::new(user_info).is_err());
+        let mut user_info = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::new();
+        user_info.try_insert(
+            InfoStr::from_bytes(b"na\"me")?,
+            InfoStr::from_bytes(b"Sarge")?,
+        )?;
+        assert!(ConnectMessage::new(user_info).is_err());
+
+        // only a case sensitive "connect" is decompressed, anything else is tokenized as is
+        let packet = ConnectionlessPacket::new(&b"CONNECT \"\\name\\Sarge\""[..])?;
+        let message = ConnectMessage::<InfoString>::parse_packet(&packet)?;
+        assert_eq!(message.user_info().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn connectmessage_to_packet() -> Result<(), Box<dyn std::error::Error>> {
+        let mut user_info = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::new();
+        user_info.try_insert(
+            InfoStr::from_bytes(b"name")?,
+            InfoStr::from_bytes(b"Sarge")?,
+        )?;
+        user_info.try_insert(
+            InfoStr::from_bytes(b"qport")?,
+            InfoStr::from_bytes(b"2033")?,
+        )?;
+
+        let packet = ConnectMessage::new(user_info)?.to_packet()?;
+        assert!(packet.payload().starts_with(b"connect \x18\x00"));
+
+        let connect_message = ConnectMessage::<InfoString>::parse_packet(&packet)?;
+        let user_info: Vec<_> = connect_message
+            .user_info()
+            .iter()
+            .map(|(k, v)| (k.as_bytes(), v.as_bytes()))
+            .collect();
+        assert_eq!(
+            user_info,
+            [(&b"name"[..], &b"Sarge"[..]), (&b"qport"[..], &b"2033"[..])]
+        );
 
         Ok(())
     }
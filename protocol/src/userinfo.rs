@@ -0,0 +1,333 @@
+//! Userinfo of game clients
+//!
+//! A game client sends its userinfo in [`crate::server::ConnectMessage`].
+//! Besides the cvars with `CVAR_USERINFO` like `name` or `model`, the engine adds the keys of [`UserInfo`] to it.
+
+use crate::{info_int, info_value, Challenge, InvalidInfoValueError, QPort};
+use quake3::info::{InfoMap, InfoStr, InfoString, LimitError, INFO_LIMIT};
+use std::ffi::{c_int, c_ushort};
+
+const PROTOCOL_KEY: &str = "protocol";
+const CHALLENGE_KEY: &str = "challenge";
+const QPORT_KEY: &str = "qport";
+const RATE_KEY: &str = "rate";
+const SNAPS_KEY: &str = "snaps";
+const ANONYMOUS_KEY: &str = "cl_anonymous";
+const VOIP_PROTOCOL_KEY: &str = "cl_voipProtocol";
+const GUID_KEY: &str = "cl_guid";
+const PASSWORD_KEY: &str = "password";
+
+// MD5 hex digest of the cl_guid
+const GUID_LEN: usize = 32;
+
+/// Error for a [`UserInfo`] setter
+#[derive(thiserror::Error, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum SetUserInfoError {
+    /// The value would not be read back by the getter
+    #[error(transparent)]
+    InvalidValue(#[from] InvalidInfoValueError),
+    #[error(transparent)]
+    Limit(#[from] LimitError<InfoString, InfoString>),
+}
+
+/// Userinfo with typed access to the keys that the engine uses
+///
+/// All other keys are kept as they are.
+/// Getters return `Ok(None)` if the key is missing, and an error if its value can not be parsed.
+pub struct UserInfo(InfoMap<InfoString, InfoString, { INFO_LIMIT }>);
+
+impl Default for UserInfo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<InfoMap<InfoString, InfoString, { INFO_LIMIT }>> for UserInfo {
+    fn from(info: InfoMap<InfoString, InfoString, { INFO_LIMIT }>) -> Self {
+        Self(info)
+    }
+}
+
+impl From<UserInfo> for InfoMap<InfoString, InfoString, { INFO_LIMIT }> {
+    fn from(user_info: UserInfo) -> Self {
+        user_info.0
+    }
+}
+
+impl UserInfo {
+    pub fn new() -> Self {
+        Self(InfoMap::new())
+    }
+
+    pub fn info(&self) -> &InfoMap<InfoString, InfoString, { INFO_LIMIT }> {
+        &self.0
+    }
+
+    fn value(&self, key: &str) -> Option<&InfoStr> {
+        info_value(&self.0, key)
+    }
+
+    fn set(
+        &mut self,
+        key: &str,
+        value: InfoString,
+    ) -> Result<(), LimitError<InfoString, InfoString>> {
+        let key = InfoString::from_bytes(key).expect("key should be valid");
+        self.0.try_insert(key, value).map(|_old| ())
+    }
+
+    fn set_int(
+        &mut self,
+        key: &str,
+        value: c_int,
+    ) -> Result<(), LimitError<InfoString, InfoString>> {
+        let value = InfoString::from_bytes(value.to_string()).expect("integer should be valid");
+        self.set(key, value)
+    }
+
+    // the userinfo is quoted in `connect`, see crate::server::ConnectMessage::new
+    fn set_unquoted(
+        &mut self,
+        key: &'static str,
+        value: InfoString,
+    ) -> Result<(), SetUserInfoError> {
+        if value.as_bytes().contains(&b'"') {
+            return Err(InvalidInfoValueError { key }.into());
+        }
+        Ok(self.set(key, value)?)
+    }
+
+    fn int(&self, key: &'static str) -> Result<Option<c_int>, InvalidInfoValueError> {
+        info_int(&self.0, key)
+    }
+
+    /// Protocol of the game client, i.e. `protocol`
+    pub fn protocol(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        self.int(PROTOCOL_KEY)
+    }
+
+    pub fn set_protocol(
+        &mut self,
+        protocol: c_int,
+    ) -> Result<(), LimitError<InfoString, InfoString>> {
+        self.set_int(PROTOCOL_KEY, protocol)
+    }
+
+    /// Challenge from `challengeResponse`, i.e. `challenge`
+    pub fn challenge(&self) -> Result<Option<Challenge>, InvalidInfoValueError> {
+        Ok(self.int(CHALLENGE_KEY)?.map(Challenge::new))
+    }
+
+    pub fn set_challenge(
+        &mut self,
+        challenge: Challenge,
+    ) -> Result<(), LimitError<InfoString, InfoString>> {
+        self.set_int(CHALLENGE_KEY, challenge.into())
+    }
+
+    /// QPort of the game client's sequenced packets, i.e. `qport`
+    pub fn qport(&self) -> Result<Option<QPort>, InvalidInfoValueError> {
+        const ERROR: InvalidInfoValueError = InvalidInfoValueError { key: QPORT_KEY };
+        self.int(QPORT_KEY)?
+            .map(|qport| {
+                c_ushort::try_from(qport)
+                    .ok()
+                    .and_then(|qport| QPort::new(qport).ok())
+                    .ok_or(ERROR)
+            })
+            .transpose()
+    }
+
+    pub fn set_qport(&mut self, qport: QPort) -> Result<(), LimitError<InfoString, InfoString>> {
+        self.set_int(QPORT_KEY, c_ushort::from(qport).into())
+    }
+
+    /// Bytes per second the game client accepts, i.e. `rate`
+    pub fn rate(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        self.int(RATE_KEY)
+    }
+
+    pub fn set_rate(&mut self, rate: c_int) -> Result<(), LimitError<InfoString, InfoString>> {
+        self.set_int(RATE_KEY, rate)
+    }
+
+    /// Snapshots per second the game client wants, i.e. `snaps`
+    pub fn snaps(&self) -> Result<Option<c_int>, InvalidInfoValueError> {
+        self.int(SNAPS_KEY)
+    }
+
+    pub fn set_snaps(&mut self, snaps: c_int) -> Result<(), LimitError<InfoString, InfoString>> {
+        self.set_int(SNAPS_KEY, snaps)
+    }
+
+    /// Whether the game client hides its identity from the auth server, i.e. `cl_anonymous`
+    pub fn anonymous(&self) -> Result<Option<bool>, InvalidInfoValueError> {
+        // like atoi(), anything but 0 is true
+        Ok(self.int(ANONYMOUS_KEY)?.map(|anonymous| anonymous != 0))
+    }
+
+    pub fn set_anonymous(
+        &mut self,
+        anonymous: bool,
+    ) -> Result<(), LimitError<InfoString, InfoString>> {
+        self.set_int(ANONYMOUS_KEY, anonymous.into())
+    }
+
+    /// VoIP codec of the game client like `opus`, i.e. `cl_voipProtocol`
+    pub fn voip_protocol(&self) -> Option<&InfoStr> {
+        self.value(VOIP_PROTOCOL_KEY)
+    }
+
+    /// Set `cl_voipProtocol`, which must not contain `"`
+    pub fn set_voip_protocol(&mut self, voip_protocol: InfoString) -> Result<(), SetUserInfoError> {
+        self.set_unquoted(VOIP_PROTOCOL_KEY, voip_protocol)
+    }
+
+    /// Hex digest which identifies the game client, i.e. `cl_guid`
+    pub fn guid(&self) -> Result<Option<&InfoStr>, InvalidInfoValueError> {
+        self.value(GUID_KEY)
+            .map(|guid| {
+                let bytes = guid.as_bytes();
+                if bytes.len() == GUID_LEN && bytes.iter().all(u8::is_ascii_hexdigit) {
+                    Ok(guid)
+                } else {
+                    Err(InvalidInfoValueError { key: GUID_KEY })
+                }
+            })
+            .transpose()
+    }
+
+    /// Set `cl_guid`, which must be a hex digest like [`Self::guid`] expects
+    pub fn set_guid(&mut self, guid: InfoString) -> Result<(), SetUserInfoError> {
+        let bytes = guid.as_bytes();
+        if bytes.len() != GUID_LEN || !bytes.iter().all(u8::is_ascii_hexdigit) {
+            return Err(InvalidInfoValueError { key: GUID_KEY }.into());
+        }
+        self.set_unquoted(GUID_KEY, guid)
+    }
+
+    /// Password of a private game server, i.e. `password`
+    pub fn password(&self) -> Option<&InfoStr> {
+        self.value(PASSWORD_KEY)
+    }
+
+    /// Set `password`, which must not contain `"`
+    pub fn set_password(&mut self, password: InfoString) -> Result<(), SetUserInfoError> {
+        self.set_unquoted(PASSWORD_KEY, password)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER_INFO: &[u8] = include_bytes!("../../tests/data/userinfo.txt");
+
+    #[test]
+    fn userinfo_getters() -> Result<(), Box<dyn std::error::Error>> {
+        let user_info = UserInfo::from(InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(
+            USER_INFO,
+        )?);
+
+        assert_eq!(user_info.protocol()?, Some(68));
+        assert_eq!(user_info.challenge()?, Some(Challenge::new(-9938504)));
+        assert_eq!(user_info.qport()?, Some(QPort::new(2033)?));
+        assert_eq!(user_info.rate()?, Some(25000));
+        assert_eq!(user_info.snaps()?, Some(20));
+        assert_eq!(user_info.anonymous()?, Some(false));
+        assert_eq!(
+            user_info.voip_protocol(),
+            Some(InfoStr::from_bytes(b"opus")?)
+        );
+        assert_eq!(
+            user_info.guid()?,
+            Some(InfoStr::from_bytes(b"D17466611282F45B65CE2FD80F83B6B0")?)
+        );
+        assert_eq!(user_info.password(), None);
+
+        // unknown keys are kept
+        assert_eq!(user_info.info().len(), 19);
+
+        Ok(())
+    }
+
+    #[test]
+    fn userinfo_invalid() -> Result<(), Box<dyn std::error::Error>> {
+        let user_info = UserInfo::from(InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(
+            b"\\qport\\0\\rate\\fast\\cl_guid\\xyz",
+        )?);
+
+        assert_eq!(user_info.qport().map_err(|e| e.key()), Err("qport"));
+        assert_eq!(user_info.rate().map_err(|e| e.key()), Err("rate"));
+        assert_eq!(user_info.guid().map_err(|e| e.key()), Err("cl_guid"));
+        assert_eq!(user_info.snaps()?, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn userinfo_setters() -> Result<(), Box<dyn std::error::Error>> {
+        let mut user_info = UserInfo::new();
+        user_info.set_protocol(71)?;
+        user_info.set_challenge(Challenge::new(-42))?;
+        user_info.set_qport(QPort::new(27960)?)?;
+        user_info.set_anonymous(true)?;
+        user_info.set_password(InfoString::from_bytes("secret")?)?;
+        user_info.set_protocol(68)?;
+        user_info.set_guid(InfoString::from_bytes("D17466611282F45B65CE2FD80F83B6B0")?)?;
+        user_info.set_voip_protocol(InfoString::from_bytes("opus")?)?;
+
+        let entries: Vec<_> = user_info
+            .info()
+            .iter()
+            .map(|(k, v)| (k.as_bytes(), v.as_bytes()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                (&b"protocol"[..], &b"68"[..]),
+                (&b"challenge"[..], &b"-42"[..]),
+                (&b"qport"[..], &b"27960"[..]),
+                (&b"cl_anonymous"[..], &b"1"[..]),
+                (&b"password"[..], &b"secret"[..]),
+                (&b"cl_guid"[..], &b"D17466611282F45B65CE2FD80F83B6B0"[..]),
+                (&b"cl_voipProtocol"[..], &b"opus"[..]),
+            ]
+        );
+        assert_eq!(user_info.anonymous()?, Some(true));
+
+        Ok(())
+    }
+
+    #[test]
+    fn userinfo_setters_invalid() -> Result<(), Box<dyn std::error::Error>> {
+        let mut user_info = UserInfo::new();
+        for guid in [
+            "xyz",
+            "D17466611282F45B65CE2FD80F83B6B",
+            "G17466611282F45B65CE2FD80F83B6B0",
+        ] {
+            assert!(matches!(
+                user_info.set_guid(InfoString::from_bytes(guid)?),
+                Err(SetUserInfoError::InvalidValue(e)) if e.key() == "cl_guid"
+            ));
+        }
+        assert!(matches!(
+            user_info.set_password(InfoString::from_bytes("sec\"ret")?),
+            Err(SetUserInfoError::InvalidValue(e)) if e.key() == "password"
+        ));
+        assert!(matches!(
+            user_info.set_voip_protocol(InfoString::from_bytes("op\"us")?),
+            Err(SetUserInfoError::InvalidValue(e)) if e.key() == "cl_voipProtocol"
+        ));
+        assert!(user_info.info().is_empty());
+
+        let long = "x".repeat(INFO_LIMIT);
+        assert!(matches!(
+            user_info.set_password(InfoString::from_bytes(long)?),
+            Err(SetUserInfoError::Limit(_))
+        ));
+
+        Ok(())
+    }
+}
@@ -1,16 +1,9 @@
 use crate::qstr::{QStr, QString};
-use winnow::combinator::preceded;
-use winnow::combinator::repeat;
-use winnow::error::ContextError;
-use winnow::error::ErrMode;
-use winnow::error::ErrorKind;
-use winnow::error::ParserError;
-use winnow::token::take_while;
-use winnow::PResult;
-use winnow::Parser;
-
-// TODO: ioQ3 also disallows ; (semicolon) and " (double quote), but at least for info de/ser they are not an issue
+
+// ioQ3 also disallows ; (semicolon) and " (double quote), but only in InfoMap::set_value_for_key
 const BACKSLASH: u8 = b'\\';
+const SEMICOLON: u8 = b';';
+const DOUBLE_QUOTE: u8 = b'"';
 
 #[repr(transparent)]
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -26,6 +19,9 @@ pub struct InfoStr(QStr);
 pub struct FromBytesError(usize);
 
 impl InfoStr {
+    /// # Safety
+    ///
+    /// `bytes` must neither contain `\0` nor `\\`
     #[inline]
     #[must_use]
     pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
@@ -58,7 +54,7 @@ impl alloc::borrow::ToOwned for InfoStr {
     type Owned = InfoString;
 
     fn to_owned(&self) -> InfoString {
-        InfoString(self.0.to_owned().into())
+        InfoString(self.0.to_owned())
     }
 }
 
@@ -79,6 +75,9 @@ pub struct InfoString(QString);
 pub struct ByteError(usize, alloc::vec::Vec<u8>);
 
 impl InfoString {
+    /// # Safety
+    ///
+    /// `bytes` must neither contain `\0` nor `\\`
     #[must_use]
     pub unsafe fn from_bytes_unchecked(bytes: alloc::vec::Vec<u8>) -> Self {
         // TODO: debug_assert
@@ -105,7 +104,7 @@ impl core::ops::Deref for InfoString {
     #[inline]
     fn deref(&self) -> &InfoStr {
         // SAFETY: ???
-        unsafe { InfoStr::from_bytes_unchecked(&self.0.as_ref()) }
+        unsafe { InfoStr::from_bytes_unchecked(self.0.as_ref()) }
     }
 }
 
@@ -156,10 +155,67 @@ pub struct InfoMap<K, V, const L: usize, S = std::collections::hash_map::RandomS
 #[cfg_attr(feature = "std", error("limit"))]
 pub struct LimitError<K, V>(K, V);
 
-#[derive(Clone, PartialEq, Eq, Debug)]
+/// Reason of a [`ParseError`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+pub enum ParseErrorKind {
+    /// The info does not start with `\\`
+    #[cfg_attr(feature = "std", error("missing backslash"))]
+    MissingBackslash,
+    /// The engine stops at `\0` like at the end of a C string
+    #[cfg_attr(feature = "std", error("NUL"))]
+    Nul,
+    #[cfg_attr(feature = "std", error("empty key"))]
+    EmptyKey,
+    /// The last key has no value
+    #[cfg_attr(feature = "std", error("missing value"))]
+    MissingValue,
+    /// The info ends with `\\` after a value
+    #[cfg_attr(feature = "std", error("trailing backslash"))]
+    TrailingBackslash,
+    #[cfg_attr(feature = "std", error("duplicate key"))]
+    DuplicateKey,
+    /// The entry does not fit into [`InfoMap::LIMIT`]
+    #[cfg_attr(feature = "std", error("limit"))]
+    Limit,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+#[cfg_attr(feature = "std", derive(thiserror::Error))]
+#[cfg_attr(feature = "std", error("{kind} at {offset}"))]
+pub struct ParseError {
+    offset: usize,
+    kind: ParseErrorKind,
+}
+
+impl ParseError {
+    fn new(offset: usize, kind: ParseErrorKind) -> Self {
+        Self { offset, kind }
+    }
+
+    /// Byte offset into the parsed info
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+}
+
+/// Error for [`InfoMap::set_value_for_key`]
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 #[cfg_attr(feature = "std", derive(thiserror::Error))]
-#[cfg_attr(feature = "std", error("can not be parsed"))]
-pub struct ParseError(());
+pub enum SetValueError {
+    /// Key or value contains `;` or `"`
+    #[cfg_attr(feature = "std", error("can not use {:?}", char::from(*.0)))]
+    InvalidByte(u8),
+    /// Key is empty, which could not be parsed again
+    #[cfg_attr(feature = "std", error("empty key"))]
+    EmptyKey,
+    #[cfg_attr(feature = "std", error("limit"))]
+    Limit,
+}
 
 impl<K, V, const L: usize, S> InfoMap<K, V, L, S> {
     // TODO: Q3 has separate defines for key / value lengths, but they are the same as for the whole info string
@@ -169,70 +225,137 @@ impl<K, V, const L: usize, S> InfoMap<K, V, L, S> {
         self.0.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
     pub fn iter(&self) -> impl core::iter::Iterator<Item = (&K, &V)> {
         self.0.iter()
     }
 }
 
-fn parse_infostr<'s>(input: &mut &'s [u8]) -> PResult<&'s InfoStr> {
-    preceded(BACKSLASH, take_while(1.., |b| b != BACKSLASH))
-        .try_map(InfoStr::from_bytes)
-        .parse_next(input)
-}
+// in lenient mode errors are collected in `skipped`, otherwise the first one is returned
+fn parse_infomap<'s, const L: usize>(
+    bytes: &'s [u8],
+    mut skipped: Option<&mut alloc::vec::Vec<ParseError>>,
+) -> core::result::Result<InfoMap<&'s InfoStr, &'s InfoStr, L>, ParseError> {
+    let lenient = skipped.is_some();
+    let mut skip = |error: ParseError| match skipped.as_deref_mut() {
+        Some(skipped) => {
+            skipped.push(error);
+            Ok(())
+        }
+        None => Err(error),
+    };
+
+    let mut info = InfoMap::new();
+
+    let mut bytes = bytes;
+    if let Some(index) = memchr::memchr(0, bytes) {
+        skip(ParseError::new(index, ParseErrorKind::Nul))?;
+        bytes = &bytes[..index];
+    }
+    if bytes.is_empty() {
+        return Ok(info);
+    }
 
-fn parse_infostr_map<'s, const L: usize>(
-) -> impl Parser<&'s [u8], InfoMap<&'s InfoStr, &'s InfoStr, L>, ContextError> {
-    move |input: &mut &'s [u8]| {
-        let entries: alloc::vec::Vec<(_, _)> =
-            repeat(0.., (parse_infostr, parse_infostr)).parse_next(input)?;
-        let mut info = InfoMap::with_capacity(entries.len());
-        for (k, v) in entries {
-            info.try_insert(k, v)
-                .map_err(|_e| ErrMode::from_error_kind(input, ErrorKind::Verify))?;
+    // Info_ValueForKey skips the leading backslash only if there is one
+    let mut key_start = if bytes[0] == BACKSLASH {
+        1
+    } else {
+        skip(ParseError::new(0, ParseErrorKind::MissingBackslash))?;
+        0
+    };
+
+    loop {
+        let Some(key_len) = memchr::memchr(BACKSLASH, &bytes[key_start..]) else {
+            let error = if key_start == bytes.len() {
+                ParseError::new(key_start - 1, ParseErrorKind::TrailingBackslash)
+            } else {
+                ParseError::new(bytes.len(), ParseErrorKind::MissingValue)
+            };
+            skip(error)?;
+            break;
+        };
+        let key_end = key_start + key_len;
+        let value_start = key_end + 1;
+        let value_end = memchr::memchr(BACKSLASH, &bytes[value_start..])
+            .map_or(bytes.len(), |value_len| value_start + value_len);
+
+        let key = InfoStr::from_bytes(&bytes[key_start..key_end]).expect("key should be valid");
+        let value =
+            InfoStr::from_bytes(&bytes[value_start..value_end]).expect("value should be valid");
+
+        // Info_ValueForKey finds the first key case insensitively
+        let duplicate = if lenient {
+            info.iter()
+                .any(|(k, _v): (&&InfoStr, _)| k.as_bytes().eq_ignore_ascii_case(key.as_bytes()))
+        } else {
+            info.contains_key(key)
+        };
+        if key.as_bytes().is_empty() {
+            skip(ParseError::new(key_start, ParseErrorKind::EmptyKey))?;
+        } else if duplicate {
+            skip(ParseError::new(key_start, ParseErrorKind::DuplicateKey))?;
+        } else if info.try_insert(key, value).is_err() {
+            skip(ParseError::new(key_start, ParseErrorKind::Limit))?;
         }
-        Ok(info)
+
+        if value_end == bytes.len() {
+            break;
+        }
+        key_start = value_end + 1;
     }
+
+    Ok(info)
 }
 
 impl<const L: usize> InfoMap<&InfoStr, &InfoStr, L> {
+    /// Parse `\\key\\value` pairs, failing on anything unusual
     pub fn parse<B: core::convert::AsRef<[u8]> + ?Sized>(
         bytes: &B,
     ) -> core::result::Result<InfoMap<&InfoStr, &InfoStr, L>, ParseError> {
-        parse_infostr_map::<L>()
-            .parse(bytes.as_ref())
-            .map_err(|_e| ParseError(()))
+        parse_infomap(bytes.as_ref(), None)
     }
-}
 
-// TODO: move into InfoKv instead of whole duplication?
-
-fn parse_infostring(input: &mut &[u8]) -> PResult<InfoString> {
-    preceded(BACKSLASH, take_while(1.., |b| b != BACKSLASH))
-        .try_map(InfoString::from_bytes)
-        .parse_next(input)
-}
-
-fn parse_infostring_map<'s, const L: usize>(
-) -> impl Parser<&'s [u8], InfoMap<InfoString, InfoString, L>, ContextError> {
-    move |input: &mut &'s [u8]| {
-        let entries: alloc::vec::Vec<(_, _)> =
-            repeat(0.., (parse_infostring, parse_infostring)).parse_next(input)?;
-        let mut info = InfoMap::with_capacity(entries.len());
-        for (k, v) in entries {
-            info.try_insert(k, v)
-                .map_err(|_e| ErrMode::from_error_kind(input, ErrorKind::Verify))?;
-        }
-        Ok(info)
+    /// Parse `\\key\\value` pairs like `Info_ValueForKey` would see them, and report what was skipped
+    ///
+    /// The info ends at the first `\0`, the leading `\\` is optional,
+    /// and only the first of duplicate keys is kept like the case insensitive lookup finds it.
+    pub fn parse_lenient<B: core::convert::AsRef<[u8]> + ?Sized>(
+        bytes: &B,
+    ) -> (InfoMap<&InfoStr, &InfoStr, L>, alloc::vec::Vec<ParseError>) {
+        let mut skipped = alloc::vec::Vec::new();
+        let info = parse_infomap(bytes.as_ref(), Some(&mut skipped))
+            .expect("lenient parsing should not fail");
+        (info, skipped)
     }
 }
 
 impl<const L: usize> InfoMap<InfoString, InfoString, L> {
+    /// See [`InfoMap::<&InfoStr, &InfoStr, L>::parse`]
     pub fn parse<B: core::convert::AsRef<[u8]> + ?Sized>(
         bytes: &B,
     ) -> core::result::Result<InfoMap<InfoString, InfoString, L>, ParseError> {
-        parse_infostring_map::<L>()
-            .parse(bytes.as_ref())
-            .map_err(|_e| ParseError(()))
+        InfoMap::<&InfoStr, &InfoStr, L>::parse(bytes).map(|info| info.to_owned())
+    }
+
+    /// See [`InfoMap::<&InfoStr, &InfoStr, L>::parse_lenient`]
+    pub fn parse_lenient<B: core::convert::AsRef<[u8]> + ?Sized>(
+        bytes: &B,
+    ) -> (
+        InfoMap<InfoString, InfoString, L>,
+        alloc::vec::Vec<ParseError>,
+    ) {
+        let (info, skipped) = InfoMap::<&InfoStr, &InfoStr, L>::parse_lenient(bytes);
+        (info.to_owned(), skipped)
+    }
+}
+
+impl<K, V, const L: usize> Default for InfoMap<K, V, L> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -277,15 +400,156 @@ where
 
     // at least the following makes the API map-ish, everything that mutates needs to be fallible to obey LIMIT
     // advanced functions could be dodged by into_hashmap() ?
-    // TODO: pub fn get<Q>(&self, key: &Q) -> Option<&V>
-    // TODO: pub fn remove<Q>(&mut self, key: &Q) -> Option<V>
     // TODO: pub fn iter(&self) -> Iter<'_, K, V>
 
-    // TODO: test that insertion and removal work like in Q3
     // TODO: are empty InfoKv valid?
 
     // TODO: read from bytes aka parse
-    // TODO: write as bytes
+}
+
+impl<K, V, const L: usize, S> InfoMap<K, V, L, S>
+where
+    K: InfoKv + core::convert::AsRef<[u8]>,
+    V: InfoKv + core::convert::AsRef<[u8]>,
+{
+    /// Length of the written info, which is what counts against [`Self::LIMIT`]
+    pub fn encoded_len(&self) -> usize {
+        self.0
+            .iter()
+            .fold(0, |acc, (k, v)| acc + k.encoded_size() + v.encoded_size())
+    }
+
+    /// Write `\\key\\value` pairs in insertion order
+    pub fn write_to(&self, buf: &mut impl bytes::BufMut) {
+        for (key, value) in self.0.iter() {
+            buf.put_u8(BACKSLASH);
+            buf.put_slice(key.as_ref());
+            buf.put_u8(BACKSLASH);
+            buf.put_slice(value.as_ref());
+        }
+    }
+
+    pub fn to_bytes(&self) -> bytes::Bytes {
+        let mut buf = bytes::BytesMut::with_capacity(self.encoded_len());
+        self.write_to(&mut buf);
+        buf.freeze()
+    }
+}
+
+impl<const L: usize, S> InfoMap<InfoString, InfoString, L, S>
+where
+    S: core::hash::BuildHasher,
+{
+    /// Remove the key that matches `key` exactly, like the `strcmp` in `Info_RemoveKey`
+    ///
+    /// This is case sensitive unlike `Info_ValueForKey`, which uses `Q_stricmp`.
+    pub fn remove_key<Q: core::convert::AsRef<[u8]> + ?Sized>(
+        &mut self,
+        key: &Q,
+    ) -> Option<(InfoString, InfoString)> {
+        let key = key.as_ref();
+        let index = self.0.keys().position(|k| k.as_bytes() == key)?;
+        self.0.shift_remove_index(index)
+    }
+
+    /// Set `key` like `Info_SetValueForKey`
+    ///
+    /// Unlike [`Self::try_insert`] this follows the engine:
+    /// - keys and values with `;` or `"` are rejected
+    /// - the old key is removed case sensitively with [`Self::remove_key`],
+    ///   so `name` and `Name` can both be set even though `Info_ValueForKey` only finds the first
+    /// - an empty value only removes the key
+    /// - the new pair is prepended, so the most recently set key comes first;
+    ///   id Quake 3 1.32 and ioq3 share this code, appending at the end is what Quake 2 does
+    /// - the info must stay below [`Self::LIMIT`] to leave room for the terminating NUL,
+    ///   if it does not the old key is still removed
+    ///
+    /// Removing case insensitively or appending would be simpler, but the info string would then
+    /// differ from what the engine writes for the same cvars.
+    pub fn set_value_for_key(
+        &mut self,
+        key: InfoString,
+        value: InfoString,
+    ) -> Result<(), SetValueError> {
+        for byte in [SEMICOLON, DOUBLE_QUOTE] {
+            if key.as_bytes().contains(&byte) || value.as_bytes().contains(&byte) {
+                return Err(SetValueError::InvalidByte(byte));
+            }
+        }
+        if key.as_bytes().is_empty() {
+            return Err(SetValueError::EmptyKey);
+        }
+
+        self.remove_key(key.as_bytes());
+        if value.as_bytes().is_empty() {
+            return Ok(());
+        }
+
+        let size = self.encoded_len() + key.encoded_size() + value.encoded_size();
+        if size >= Self::LIMIT {
+            return Err(SetValueError::Limit);
+        }
+        self.0.shift_insert(0, key, value);
+        Ok(())
+    }
+}
+
+/// Lossy for bytes that are not UTF-8
+impl<K, V, const L: usize, S> core::fmt::Display for InfoMap<K, V, L, S>
+where
+    K: core::convert::AsRef<[u8]>,
+    V: core::convert::AsRef<[u8]>,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (key, value) in self.0.iter() {
+            write!(
+                f,
+                "\\{}\\{}",
+                alloc::string::String::from_utf8_lossy(key.as_ref()),
+                alloc::string::String::from_utf8_lossy(value.as_ref())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// lookup key for any InfoKv, they all hash like their bytes
+// not Eq, which would conflict with the blanket Equivalent impl
+#[repr(transparent)]
+#[derive(Hash)]
+struct KeyBytes([u8]);
+
+impl KeyBytes {
+    fn new(bytes: &[u8]) -> &Self {
+        // SAFETY: KeyBytes is repr(transparent) over [u8]
+        unsafe { &*(bytes as *const [u8] as *const Self) }
+    }
+}
+
+impl<K: core::convert::AsRef<[u8]>> indexmap::Equivalent<K> for KeyBytes {
+    fn equivalent(&self, key: &K) -> bool {
+        self.0 == *key.as_ref()
+    }
+}
+
+impl<K, V, const L: usize, S> InfoMap<K, V, L, S>
+where
+    K: core::hash::Hash + core::cmp::Eq + core::convert::AsRef<[u8]>,
+    S: core::hash::BuildHasher,
+{
+    /// Value for `key`, which can be e.g. `&InfoStr` or `&[u8]`
+    pub fn get<Q: core::convert::AsRef<[u8]> + ?Sized>(&self, key: &Q) -> Option<&V> {
+        self.0.get(KeyBytes::new(key.as_ref()))
+    }
+
+    pub fn contains_key<Q: core::convert::AsRef<[u8]> + ?Sized>(&self, key: &Q) -> bool {
+        self.0.contains_key(KeyBytes::new(key.as_ref()))
+    }
+
+    /// Remove `key` and keep the order of the other entries, this frees its encoded size for [`Self::try_insert`]
+    pub fn remove<Q: core::convert::AsRef<[u8]> + ?Sized>(&mut self, key: &Q) -> Option<V> {
+        self.0.shift_remove(KeyBytes::new(key.as_ref()))
+    }
 }
 
 impl<K: ?Sized, V: ?Sized, const L: usize> InfoMap<&K, &V, L>
@@ -319,6 +583,73 @@ pub type Info = InfoMap<InfoString, InfoString, INFO_LIMIT>;
 
 pub type BigInfo = InfoMap<InfoString, InfoString, INFO_BIG_LIMIT>;
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for InfoStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for InfoString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for InfoString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = deserializer.deserialize_byte_buf(crate::qstr::ByteBufVisitor)?;
+        Self::from_bytes(bytes)
+            .map_err(|e| serde::de::Error::custom(format_args!("NUL or backslash at {}", e.0)))
+    }
+}
+
+/// Serialized as a map in insertion order
+#[cfg(feature = "serde")]
+impl<K, V, const L: usize, S> serde::Serialize for InfoMap<K, V, L, S>
+where
+    K: serde::Serialize,
+    V: serde::Serialize,
+{
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_map(self.0.iter())
+    }
+}
+
+#[cfg(feature = "serde")]
+struct InfoMapVisitor<const L: usize>;
+
+#[cfg(feature = "serde")]
+impl<'de, const L: usize> serde::de::Visitor<'de> for InfoMapVisitor<L> {
+    type Value = InfoMap<InfoString, InfoString, L>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(formatter, "a map of at most {} encoded bytes", L)
+    }
+
+    fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut info = InfoMap::new();
+        while let Some((key, value)) = map.next_entry::<InfoString, InfoString>()? {
+            if info.contains_key(&key) {
+                return Err(serde::de::Error::custom("duplicate key"));
+            }
+            info.try_insert(key, value)
+                .map_err(|_| serde::de::Error::custom("limit"))?;
+        }
+        Ok(info)
+    }
+}
+
+/// Deserialized with the same checks as [`InfoMap::try_insert`]
+#[cfg(feature = "serde")]
+impl<'de, const L: usize> serde::Deserialize<'de> for InfoMap<InfoString, InfoString, L> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_map(InfoMapVisitor)
+    }
+}
+
 mod private {
     pub trait Sealed {}
 
@@ -393,6 +724,318 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn infomap_get() -> Result<(), Box<dyn std::error::Error>> {
+        let info = Info::parse(b"\\name\\UnnamedPlayer\\rate\\25000")?;
+
+        assert_eq!(
+            info.get(InfoStr::from_bytes(b"name")?)
+                .map(|v| v.as_bytes()),
+            Some(&b"UnnamedPlayer"[..])
+        );
+        assert_eq!(
+            info.get(&b"rate"[..]).map(|v| v.as_bytes()),
+            Some(&b"25000"[..])
+        );
+        assert_eq!(info.get("Name"), None);
+        assert!(info.contains_key("rate"));
+        assert!(!info.contains_key("snaps"));
+
+        let borrowed = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::parse(b"\\name\\UnnamedPlayer")?;
+        assert_eq!(
+            borrowed.get("name").map(|v| v.as_bytes()),
+            Some(&b"UnnamedPlayer"[..])
+        );
+        assert!(borrowed.contains_key(InfoStr::from_bytes(b"name")?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn infomap_remove() -> Result<(), Box<dyn std::error::Error>> {
+        let mut info: InfoMap<InfoString, InfoString, 18> = InfoMap::new();
+        info.try_insert(
+            InfoString::from_bytes(b"k0")?,
+            InfoString::from_bytes(b"vA")?,
+        )?;
+        info.try_insert(
+            InfoString::from_bytes(b"k1")?,
+            InfoString::from_bytes(b"vB")?,
+        )?;
+        info.try_insert(
+            InfoString::from_bytes(b"k2")?,
+            InfoString::from_bytes(b"vC")?,
+        )?;
+        // \k0\vA\k1\vB\k2\vC == 18 == limit
+        assert!(info
+            .try_insert(
+                InfoString::from_bytes(b"k3")?,
+                InfoString::from_bytes(b"vD")?
+            )
+            .is_err());
+
+        assert_eq!(info.remove("k0"), Some(InfoString::from_bytes(b"vA")?));
+        assert_eq!(info.remove("k0"), None);
+
+        // the removed entry no longer counts against the limit
+        info.try_insert(
+            InfoString::from_bytes(b"k3")?,
+            InfoString::from_bytes(b"vD")?,
+        )?;
+
+        let keys: Vec<_> = info.iter().map(|(k, _v)| k.as_bytes()).collect();
+        assert_eq!(keys, [&b"k1"[..], b"k2", b"k3"]);
+
+        let mut borrowed: InfoMap<&InfoStr, &InfoStr, 18> = InfoMap::new();
+        borrowed.try_insert(InfoStr::from_bytes(b"k0")?, InfoStr::from_bytes(b"vA")?)?;
+        assert_eq!(
+            borrowed.remove(InfoStr::from_bytes(b"k0")?),
+            Some(InfoStr::from_bytes(b"vA")?)
+        );
+        assert!(borrowed.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn infomap_write_to() -> Result<(), Box<dyn std::error::Error>> {
+        let mut info: InfoMap<InfoString, InfoString, 13> = InfoMap::new();
+        info.try_insert(
+            InfoString::from_bytes(b"k1")?,
+            InfoString::from_bytes(b"vB")?,
+        )?;
+        info.try_insert(InfoString::from_bytes(b"k0")?, InfoString::from_bytes(b"")?)?;
+
+        let bytes = info.to_bytes();
+        assert_eq!(&bytes[..], b"\\k1\\vB\\k0\\");
+        assert_eq!(bytes.len(), info.encoded_len());
+        assert_eq!(info.to_string(), "\\k1\\vB\\k0\\");
+
+        let parsed = InfoMap::<InfoString, InfoString, 13>::parse(&bytes)?;
+        assert!(parsed.iter().eq(info.iter()));
+
+        let borrowed = InfoMap::<&InfoStr, &InfoStr, 13>::parse(&bytes)?;
+        assert_eq!(borrowed.to_bytes(), bytes);
+
+        assert_eq!(Info::new().to_bytes().len(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn infomap_display_lossy() -> Result<(), Box<dyn std::error::Error>> {
+        let info = Info::parse(b"\\name\\\xFFplayer")?;
+        assert_eq!(info.to_string(), "\\name\\\u{FFFD}player");
+
+        Ok(())
+    }
+
+    // CL_CheckForResend() sets these on Cvar_InfoString(CVAR_USERINFO)
+    const USER_INFO: &[u8] = include_bytes!("../../tests/data/userinfo.txt");
+
+    #[test]
+    fn infomap_setvalueforkey_engine() -> Result<(), Box<dyn std::error::Error>> {
+        let cvars = &USER_INFO[b"\\challenge\\-9938504\\qport\\2033\\protocol\\68".len()..];
+        let mut info = Info::parse(cvars)?;
+
+        info.set_value_for_key(
+            InfoString::from_bytes(b"protocol")?,
+            InfoString::from_bytes(b"68")?,
+        )?;
+        info.set_value_for_key(
+            InfoString::from_bytes(b"qport")?,
+            InfoString::from_bytes(b"2033")?,
+        )?;
+        info.set_value_for_key(
+            InfoString::from_bytes(b"challenge")?,
+            InfoString::from_bytes(b"-9938504")?,
+        )?;
+        assert_eq!(&info.to_bytes()[..], USER_INFO);
+
+        Ok(())
+    }
+
+    #[test]
+    fn infomap_setvalueforkey() -> Result<(), Box<dyn std::error::Error>> {
+        let mut info = Info::parse(b"\\name\\UnnamedPlayer\\rate\\25000\\snaps\\20")?;
+
+        // replaced keys move to the front
+        info.set_value_for_key(
+            InfoString::from_bytes(b"rate")?,
+            InfoString::from_bytes(b"90000")?,
+        )?;
+        assert_eq!(
+            &info.to_bytes()[..],
+            b"\\rate\\90000\\name\\UnnamedPlayer\\snaps\\20"
+        );
+
+        // keys are only replaced case sensitively, like the strcmp in Info_RemoveKey
+        info.set_value_for_key(
+            InfoString::from_bytes(b"Rate")?,
+            InfoString::from_bytes(b"25000")?,
+        )?;
+        assert_eq!(
+            &info.to_bytes()[..],
+            b"\\Rate\\25000\\rate\\90000\\name\\UnnamedPlayer\\snaps\\20"
+        );
+
+        // empty values remove the key
+        info.set_value_for_key(
+            InfoString::from_bytes(b"NAME")?,
+            InfoString::from_bytes(b"")?,
+        )?;
+        info.set_value_for_key(
+            InfoString::from_bytes(b"name")?,
+            InfoString::from_bytes(b"")?,
+        )?;
+        assert_eq!(
+            &info.to_bytes()[..],
+            b"\\Rate\\25000\\rate\\90000\\snaps\\20"
+        );
+
+        assert_eq!(
+            info.set_value_for_key(
+                InfoString::from_bytes(b"name")?,
+                InfoString::from_bytes(b"a;b")?
+            ),
+            Err(SetValueError::InvalidByte(b';'))
+        );
+        assert_eq!(
+            info.set_value_for_key(
+                InfoString::from_bytes(b"snaps\"")?,
+                InfoString::from_bytes(b"40")?
+            ),
+            Err(SetValueError::InvalidByte(b'"'))
+        );
+        assert_eq!(
+            info.set_value_for_key(InfoString::from_bytes(b"")?, InfoString::from_bytes(b"x")?),
+            Err(SetValueError::EmptyKey)
+        );
+        assert_eq!(
+            &info.to_bytes()[..],
+            b"\\Rate\\25000\\rate\\90000\\snaps\\20"
+        );
+
+        assert_eq!(
+            info.remove_key("rate"),
+            Some((
+                InfoString::from_bytes(b"rate")?,
+                InfoString::from_bytes(b"90000")?
+            ))
+        );
+        assert_eq!(info.remove_key("rate"), None);
+        assert_eq!(&info.to_bytes()[..], b"\\Rate\\25000\\snaps\\20");
+
+        Ok(())
+    }
+
+    #[test]
+    fn infomap_setvalueforkey_limit() -> Result<(), Box<dyn std::error::Error>> {
+        // \k0\vA\k1\vB == 12, which needs 13 with the terminating NUL
+        let mut info: InfoMap<InfoString, InfoString, 13> = InfoMap::new();
+        info.set_value_for_key(
+            InfoString::from_bytes(b"k0")?,
+            InfoString::from_bytes(b"vA")?,
+        )?;
+        info.set_value_for_key(
+            InfoString::from_bytes(b"k1")?,
+            InfoString::from_bytes(b"vB")?,
+        )?;
+        assert_eq!(info.encoded_len(), 12);
+
+        // the old value is removed even though the new one does not fit
+        assert_eq!(
+            info.set_value_for_key(
+                InfoString::from_bytes(b"k0")?,
+                InfoString::from_bytes(b"vAA")?
+            ),
+            Err(SetValueError::Limit)
+        );
+        assert_eq!(&info.to_bytes()[..], b"\\k1\\vB");
+
+        let mut info: InfoMap<InfoString, InfoString, 12> = InfoMap::new();
+        info.set_value_for_key(
+            InfoString::from_bytes(b"k0")?,
+            InfoString::from_bytes(b"vA")?,
+        )?;
+        assert_eq!(
+            info.set_value_for_key(
+                InfoString::from_bytes(b"k1")?,
+                InfoString::from_bytes(b"vB")?
+            ),
+            Err(SetValueError::Limit)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn infomap_parse_errors() {
+        fn error(bytes: &[u8]) -> Option<(usize, ParseErrorKind)> {
+            InfoMap::<&InfoStr, &InfoStr, 16>::parse(bytes)
+                .err()
+                .map(|e| (e.offset(), e.kind()))
+        }
+
+        assert_eq!(error(b"\\k\\v"), None);
+        assert_eq!(error(b"\\k\\"), None);
+        assert_eq!(error(b"k\\v"), Some((0, ParseErrorKind::MissingBackslash)));
+        assert_eq!(error(b"\\k\\v\0"), Some((4, ParseErrorKind::Nul)));
+        assert_eq!(error(b"\\\\v"), Some((1, ParseErrorKind::EmptyKey)));
+        assert_eq!(
+            error(b"\\k\\v\\k2"),
+            Some((7, ParseErrorKind::MissingValue))
+        );
+        assert_eq!(
+            error(b"\\k\\v\\"),
+            Some((4, ParseErrorKind::TrailingBackslash))
+        );
+        assert_eq!(error(b"\\"), Some((0, ParseErrorKind::TrailingBackslash)));
+        assert_eq!(
+            error(b"\\k\\v\\k\\w"),
+            Some((5, ParseErrorKind::DuplicateKey))
+        );
+        assert_eq!(
+            error(b"\\k0\\vA\\k1\\vB\\k2\\vC"),
+            Some((13, ParseErrorKind::Limit))
+        );
+    }
+
+    #[test]
+    fn infomap_parse_lenient() -> Result<(), Box<dyn std::error::Error>> {
+        let (info, skipped) = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::parse_lenient(
+            b"name\\player\\\\x\\Name\\other\\rate\\25000\\snaps\0\\20",
+        );
+        let entries: Vec<_> = info
+            .iter()
+            .map(|(k, v)| (k.as_bytes(), v.as_bytes()))
+            .collect();
+        assert_eq!(
+            entries,
+            [(&b"name"[..], &b"player"[..]), (b"rate", b"25000")]
+        );
+        let skipped: Vec<_> = skipped.iter().map(|e| (e.offset(), e.kind())).collect();
+        assert_eq!(
+            skipped,
+            [
+                (42, ParseErrorKind::Nul),
+                (0, ParseErrorKind::MissingBackslash),
+                (12, ParseErrorKind::EmptyKey),
+                (15, ParseErrorKind::DuplicateKey),
+                (42, ParseErrorKind::MissingValue),
+            ]
+        );
+
+        let (info, skipped) = Info::parse_lenient(b"\\k\\v\\");
+        assert_eq!(info.get("k").map(|v| v.as_bytes()), Some(&b"v"[..]));
+        assert_eq!(
+            skipped,
+            [ParseError::new(4, ParseErrorKind::TrailingBackslash)]
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn infomap_toowned() -> Result<(), Box<dyn std::error::Error>> {
         let mut borrowed: InfoMap<&InfoStr, &InfoStr, 42> = InfoMap::new();
@@ -402,8 +1045,10 @@ mod tests {
         borrowed.try_insert(InfoStr::from_bytes(b"k2")?, InfoStr::from_bytes(b"vC")?)?;
 
         let owned: InfoMap<InfoString, InfoString, 42> = borrowed.to_owned();
-        // TODO: assert owned contains owned instances of borrowed once API permits
         assert_eq!(borrowed.len(), owned.len());
+        for (&k, &v) in borrowed.iter() {
+            assert_eq!(owned.get(k).map(|v| &**v), Some(v));
+        }
 
         Ok(())
     }
@@ -420,8 +1065,7 @@ mod tests {
         info.try_insert(InfoStr::from_bytes(b"k2")?, InfoStr::from_bytes(b"vC")?)?;
 
         let parsed = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::parse(b"\\k0\\vA\\k1\\vB\\k2\\vC")?;
-        assert_eq!(info.len(), parsed.len());
-        // TODO: assert_eq!
+        assert!(info.iter().eq(parsed.iter()));
 
         Ok(())
     }
@@ -448,8 +1092,45 @@ mod tests {
 
         let parsed =
             InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(b"\\k0\\vA\\k1\\vB\\k2\\vC")?;
-        assert_eq!(info.len(), parsed.len());
-        // TODO: assert_eq!
+        assert!(info.iter().eq(parsed.iter()));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn infomap_serde() -> Result<(), Box<dyn std::error::Error>> {
+        let info = InfoMap::<InfoString, InfoString, INFO_LIMIT>::parse(
+            b"\\sv_hostname\\noname\\g_gametype\\0\\mapname\\q3dm17",
+        )?;
+        let json = serde_json::to_string(&info)?;
+        assert_eq!(
+            json,
+            r#"{"sv_hostname":"noname","g_gametype":"0","mapname":"q3dm17"}"#
+        );
+        let deserialized =
+            serde_json::from_str::<InfoMap<InfoString, InfoString, INFO_LIMIT>>(&json)?;
+        assert!(info.iter().eq(deserialized.iter()));
+
+        let borrowed = InfoMap::<&InfoStr, &InfoStr, INFO_LIMIT>::parse(b"\\k\\\xff")?;
+        assert_eq!(serde_json::to_string(&borrowed)?, r#"{"k":[255]}"#);
+
+        assert!(serde_json::from_str::<InfoString>(r#""a\\b""#).is_err());
+        assert!(serde_json::from_str::<InfoString>(r#""a\u0000b""#).is_err());
+        assert!(
+            serde_json::from_str::<InfoMap<InfoString, InfoString, INFO_LIMIT>>(r#"{"a\\b":"c"}"#)
+                .is_err()
+        );
+        assert!(serde_json::from_str::<InfoMap<InfoString, InfoString, 8>>(
+            r#"{"k0":"vA","k1":"vB"}"#
+        )
+        .is_err());
+        assert!(
+            serde_json::from_str::<InfoMap<InfoString, InfoString, INFO_LIMIT>>(
+                r#"{"k":"v","k":"w"}"#
+            )
+            .is_err()
+        );
 
         Ok(())
     }
@@ -9,6 +9,9 @@ pub struct QStr([u8]);
 pub struct FromBytesNulError(/* FIXME: */ pub(crate) usize);
 
 impl QStr {
+    /// # Safety
+    ///
+    /// `bytes` must not contain `\0`
     #[inline]
     #[must_use]
     pub unsafe fn from_bytes_unchecked(bytes: &[u8]) -> &Self {
@@ -32,12 +35,16 @@ impl QStr {
     #[inline(always)]
     pub const fn as_bytes(&self) -> &[u8] {
         // SAFETY: const sound because we transmute two types with the same layout
-        unsafe { core::intrinsics::transmute(self) }
+        unsafe { core::mem::transmute(self) }
     }
 
     pub const fn len(&self) -> usize {
         self.0.len()
     }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
 impl alloc::borrow::ToOwned for QStr {
@@ -69,6 +76,9 @@ pub struct NulError(
 );
 
 impl QString {
+    /// # Safety
+    ///
+    /// `bytes` must not contain `\0`
     #[must_use]
     pub unsafe fn from_bytes_unchecked(bytes: alloc::vec::Vec<u8>) -> Self {
         // TODO: debug_assert
@@ -117,6 +127,70 @@ where
     }
 }
 
+/// Serialized as a string if the bytes are UTF-8, as bytes otherwise
+#[cfg(feature = "serde")]
+impl serde::Serialize for QStr {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match core::str::from_utf8(self.as_bytes()) {
+            Ok(s) => serializer.serialize_str(s),
+            Err(_) => serializer.serialize_bytes(self.as_bytes()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for QString {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (**self).serialize(serializer)
+    }
+}
+
+/// Accepts both strings and bytes, like they are serialized
+#[cfg(feature = "serde")]
+pub(crate) struct ByteBufVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> serde::de::Visitor<'de> for ByteBufVisitor {
+    type Value = alloc::vec::Vec<u8>;
+
+    fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
+        formatter.write_str("a string or bytes")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(v.as_bytes().to_vec())
+    }
+
+    fn visit_string<E: serde::de::Error>(self, v: alloc::string::String) -> Result<Self::Value, E> {
+        Ok(v.into_bytes())
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: serde::de::Error>(self, v: alloc::vec::Vec<u8>) -> Result<Self::Value, E> {
+        Ok(v)
+    }
+
+    fn visit_seq<A: serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = alloc::vec::Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        Ok(bytes)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for QString {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let bytes = deserializer.deserialize_byte_buf(ByteBufVisitor)?;
+        Self::from_bytes(bytes)
+            .map_err(|e| serde::de::Error::custom(format_args!("NUL at {}", e.0)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -138,4 +212,24 @@ mod tests {
 
         assert!(QString::from_bytes(b"lorem ipsum\0".to_vec()).is_err());
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn qstring_serde() -> Result<(), Box<dyn std::error::Error>> {
+        let qstring = QString::from_bytes("lorem ipsum")?;
+        assert_eq!(serde_json::to_string(&qstring)?, r#""lorem ipsum""#);
+        assert_eq!(
+            serde_json::from_str::<QString>(r#""lorem ipsum""#)?,
+            qstring
+        );
+
+        let qstring = QString::from_bytes(b"\xff".to_vec())?;
+        assert_eq!(serde_json::to_string(&qstring)?, "[255]");
+        assert_eq!(serde_json::from_str::<QString>("[255]")?, qstring);
+
+        assert!(serde_json::from_str::<QString>(r#""lorem\u0000ipsum""#).is_err());
+        assert!(serde_json::from_str::<QString>("[108, 0]").is_err());
+
+        Ok(())
+    }
 }